@@ -0,0 +1,62 @@
+// Run with `cargo bench --bench flow_field`
+use std::hint::black_box;
+use std::time::Instant;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+#[path = "../src/flow_field.rs"]
+mod flow_field;
+
+use flow_field::FlowField;
+
+// Same footprint as the 3x3 block of loaded map chunks
+const BOUNDS: Vec2 = Vec2::new(2672.0 * 4.0 * 3.0, 1312.0 * 4.0 * 3.0);
+const OBSTACLES: usize = 300;
+const ROUNDS: u32 = 20;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let bounds = Rect::from_center_size(Vec2::ZERO, BOUNDS);
+
+    let obstacles: Vec<Rect> = (0..OBSTACLES)
+        .map(|_| {
+            let center = Vec2::new(
+                rng.gen_range(bounds.min.x..bounds.max.x),
+                rng.gen_range(bounds.min.y..bounds.max.y));
+            Rect::from_center_size(center, Vec2::new(rng.gen_range(60.0..320.0), rng.gen_range(60.0..140.0)))
+        })
+        .collect();
+
+    let mut field = FlowField::default();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        field.resize(bounds, obstacles.iter());
+    }
+    println!("resize ({}x{} cells): {:?}", field.width, field.height, start.elapsed() / ROUNDS);
+
+    // Walk the target across cells like a moving player would
+    let start = Instant::now();
+    for round in 0..ROUNDS {
+        field.build(Vec2::new(round as f32 * FlowField::CELL_SIZE, 0.));
+    }
+    println!("build ({} cell window): {:?}", FlowField::WINDOW * 2 + 1, start.elapsed() / ROUNDS);
+
+    for enemies in [1_000, 5_000, 10_000, 50_000] {
+        let positions: Vec<Vec2> = (0..enemies)
+            .map(|_| Vec2::new(
+                    rng.gen_range(bounds.min.x..bounds.max.x),
+                    rng.gen_range(bounds.min.y..bounds.max.y)))
+            .collect();
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            for position in positions.iter() {
+                black_box(field.sample(*position));
+            }
+        }
+        println!("sample {} enemies: {:?}", enemies, start.elapsed() / ROUNDS);
+    }
+}
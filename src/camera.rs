@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::{CameraController, Enemy, Player, RunState, Settings};
+use crate::events::{DamageApplied, ScreenShake};
+use crate::MouseCoords;
+
+// Trauma added when the player takes a hit
+const PLAYER_HIT_TRAUMA: f32 = 0.35;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (add_trauma, camera_follow_player.after(add_trauma)).run_if(in_state(RunState::Playing)));
+    }
+}
+
+fn add_trauma(
+    mut shake_events: EventReader<ScreenShake>,
+    mut applied_events: EventReader<DamageApplied>,
+    player_query: Query<(), With<Player>>,
+    mut camera_query: Query<&mut CameraController>,
+    settings: Res<Settings>,
+) {
+    if !settings.screen_shake {
+        shake_events.clear();
+        applied_events.clear();
+        return;
+    }
+
+    if let Ok(mut controller) = camera_query.get_single_mut() {
+        for event in shake_events.read() {
+            controller.add_trauma(event.trauma);
+        }
+
+        for event in applied_events.read() {
+            if player_query.contains(event.target) {
+                controller.add_trauma(PLAYER_HIT_TRAUMA);
+            }
+        }
+    }
+}
+
+pub fn camera_follow_player(
+    time: Res<Time>,
+    mouse_coords: Res<MouseCoords>,
+    player_query: Query<&Transform, (With<Player>, Without<CameraController>)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<CameraController>)>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection, &mut CameraController)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let Ok((mut camera_transform, mut projection, mut controller)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let delta = time.delta_seconds();
+    let player_position = player_transform.translation.truncate();
+
+    // Lead the camera a little towards where the player is aiming
+    let mouse_position = Vec2::new(mouse_coords.x, mouse_coords.y);
+    let look_ahead = ((mouse_position - player_position) * controller.look_ahead).clamp_length_max(controller.max_look_ahead);
+    let mut target = player_position + look_ahead;
+
+    // Zoom out as the horde around the player grows
+    let nearby = enemy_query
+        .iter()
+        .filter(|transform| transform.translation.truncate().distance(player_position) < controller.density_radius)
+        .count();
+    let density = (nearby as f32 / controller.density_for_max_zoom).min(1.0);
+    let target_zoom = controller.min_zoom + (controller.max_zoom - controller.min_zoom) * density;
+    projection.scale += (target_zoom - projection.scale) * (controller.zoom_speed * delta).min(1.0);
+
+    if let Some(bounds) = controller.bounds {
+        // Keep the view inside the bounds, or centre on them if the view is larger
+        let half_view = projection.area.size() / 2.0;
+        let min = bounds.min + half_view;
+        let max = bounds.max - half_view;
+
+        target.x = if min.x < max.x { target.x.clamp(min.x, max.x) } else { bounds.center().x };
+        target.y = if min.y < max.y { target.y.clamp(min.y, max.y) } else { bounds.center().y };
+    }
+
+    // Critically damped spring, settles on the target without overshooting
+    let omega = controller.frequency;
+    let offset = controller.position - target;
+    let acceleration = -omega * omega * offset - 2.0 * omega * controller.velocity;
+    controller.velocity += acceleration * delta;
+    let velocity = controller.velocity;
+    controller.position += velocity * delta;
+
+    // Shake grows with the square of trauma so small bumps stay subtle
+    controller.trauma = (controller.trauma - controller.trauma_decay * delta).max(0.0);
+    let shake = controller.trauma * controller.trauma;
+    let mut rng = rand::thread_rng();
+    let shake_offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * controller.max_shake * shake;
+    let shake_roll = rng.gen_range(-1.0..1.0) * controller.max_roll * shake;
+
+    camera_transform.translation.x = controller.position.x + shake_offset.x;
+    camera_transform.translation.y = controller.position.y + shake_offset.y;
+    camera_transform.rotation = Quat::from_rotation_z(shake_roll);
+}
@@ -1,26 +1,147 @@
 use std::collections::HashMap;
 use bevy::prelude::*;
-use crate::{components::{Collider, Enemy, Health, Invulnerability, Line, Player, PointMarker}, CollisionEvent, ENEMY_SPEED};
+use crate::{components::{AbilitySource, Bigfoot, Collider, DamageKind, Enemy, Invulnerability, Line, ObstacleGrid, OnHit, Owner, Pickup, Player, PointMarker, RunState, StaticCollider}, CollisionEvent, DamageEvent, ENEMY_SPEED};
+
+// How close to a face still counts as touching it
+const FACE_EPSILON: f32 = 1e-3;
+
+// Bodies that obstacles push back out
+type Solid = Or<(With<Player>, With<Enemy>)>;
+
+// A damaging entity and who and what it credits its hits to
+type Hitbox = (&'static Collider, Option<&'static OnHit>, Option<&'static Owner>, Option<&'static AbilitySource>);
 
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-         app.add_systems(Update, (detect_collisions, handle_collisions,));
+         app.init_resource::<ObstacleGrid>()
+             .add_systems(Update, (
+                     index_obstacles,
+                     resolve_obstacle_collisions.after(index_obstacles),
+                     detect_collisions.after(resolve_obstacle_collisions),
+                     handle_collisions,
+             ).run_if(in_state(RunState::Playing)));
     }    
 }
 
-fn detect_collisions(
-    mut query: Query<(Entity, &Transform, &mut Collider, Option<&Player>, Option<&Enemy>, Option<&Line>, Option<&PointMarker>), Without<Invulnerability>>,
+// Slab test between the segment start..end and an axis aligned rect
+pub fn segment_intersects_rect(start: Vec2, end: Vec2, rect: Rect) -> bool {
+    segment_entry(start, end, rect).is_some()
+}
+
+// Fraction along start..end where the segment first enters the rect
+pub fn segment_entry(start: Vec2, end: Vec2, rect: Rect) -> Option<f32> {
+    let delta = end - start;
+    let mut t_min: f32 = 0.;
+    let mut t_max: f32 = 1.;
+
+    for axis in 0..2 {
+        if delta[axis].abs() < f32::EPSILON {
+            if start[axis] < rect.min[axis] || start[axis] > rect.max[axis] {
+                return None;
+            }
+        } else {
+            let t1 = (rect.min[axis] - start[axis]) / delta[axis];
+            let t2 = (rect.max[axis] - start[axis]) / delta[axis];
+
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+
+            if t_min > t_max {
+                return None;
+            }
+        }
+    }
+
+    // Starting flush on a face and moving along or away from it isn't entering,
+    // push_out_of_obstacles leaves things exactly there
+    if t_min <= 0. {
+        for axis in 0..2 {
+            let on_min = (start[axis] - rect.min[axis]).abs() <= FACE_EPSILON;
+            let on_max = (start[axis] - rect.max[axis]).abs() <= FACE_EPSILON;
+
+            if (on_min && delta[axis] <= 0.) || (on_max && delta[axis] >= 0.) {
+                return None;
+            }
+        }
+    }
+
+    Some(t_min)
+}
+
+// Earliest obstacle hit by something of the given radius moving start..end
+pub fn first_obstacle_hit(grid: &ObstacleGrid, start: Vec2, end: Vec2, radius: f32) -> Option<f32> {
+    let area = Rect::from_corners(start, end).inflate(radius);
+
+    grid.query(area)
+        .filter_map(|rect| segment_entry(start, end, rect.inflate(radius)))
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+// Moves a box out of every obstacle it overlaps along the shallowest axis
+pub fn push_out_of_obstacles(grid: &ObstacleGrid, position: Vec2, size: Vec2) -> Vec2 {
+    let mut position = position;
+    let obstacles: Vec<Rect> = grid.query(Rect::from_center_size(position, size)).copied().collect();
+
+    for obstacle in obstacles {
+        let overlap = Rect::from_center_size(position, size).intersect(obstacle);
+        if overlap.is_empty() {
+            continue;
+        }
+
+        let away = position - obstacle.center();
+        if overlap.width() < overlap.height() {
+            position.x += overlap.width() * away.x.signum();
+        } else {
+            position.y += overlap.height() * away.y.signum();
+        }
+    }
+
+    position
+}
+
+fn index_obstacles(
+    mut grid: ResMut<ObstacleGrid>,
+    added_query: Query<(), Added<StaticCollider>>,
+    mut removed: RemovedComponents<StaticCollider>,
+    query: Query<&StaticCollider>,
+) {
+    // Chunks come and go rarely, so a full rebuild keeps this simple
+    if added_query.is_empty() && removed.read().count() == 0 {
+        return;
+    }
+
+    grid.cells.clear();
+    for collider in query.iter() {
+        grid.insert(collider.rect);
+    }
+}
+
+fn resolve_obstacle_collisions(
+    grid: Res<ObstacleGrid>,
+    mut query: Query<(&mut Transform, &Collider), Solid>,
+) {
+    for (mut transform, collider) in query.iter_mut() {
+        let position = push_out_of_obstacles(&grid, transform.translation.truncate(), collider.size);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+pub fn detect_collisions(
+    mut query: Query<(Entity, &Transform, &mut Collider, Option<&Player>, Option<&Enemy>, Option<&Line>, Option<&PointMarker>, Option<&Pickup>, Has<Bigfoot>)>,
     mut events: EventWriter<CollisionEvent>,
 ) {
     let mut collisions: HashMap<Entity, Vec<Entity>> = HashMap::new();
 
-    for (entity_a, transform_a, collider_a, player_a, _, line_a, point_marker_a) in query.iter() {
+    for (entity_a, transform_a, collider_a, player_a, _, line_a, point_marker_a, _, _) in query.iter() {
         let rect_a = Rect::from_center_size(transform_a.translation.truncate(), collider_a.size);
 
-        for (entity_b, transform_b, collider_b, _, enemy_b, _, _) in query.iter() {
+        for (entity_b, transform_b, collider_b, _, enemy_b, _, _, pickup_b, bigfoot_b) in query.iter() {
             let rect_b = Rect::from_center_size(transform_b.translation.truncate(), collider_b.size);
+            // The player's attacks can hurt the boss, touching it doesn't
+            let attackable_b = enemy_b.is_some() || bigfoot_b;
 
             if entity_b == entity_a {
                 continue;
@@ -32,49 +153,66 @@ fn detect_collisions(
 
                     collisions.entry(entity_a).or_default().push(entity_b);
 
-                } else if line_a.is_some() && enemy_b.is_some() {
+                } else if (line_a.is_some() || point_marker_a.is_some()) && attackable_b {
                     events.send(CollisionEvent::Damage(entity_a));
 
                     collisions.entry(entity_a).or_default().push(entity_b);
 
-                } else if point_marker_a.is_some() && enemy_b.is_some() {
-                    events.send(CollisionEvent::Damage(entity_a));
-
-                    collisions.entry(entity_a).or_default().push(entity_b);
+                } else if player_a.is_some() && pickup_b.is_some() {
+                    // Pickups stay out of the player's collision list so they never cause knockback
+                    events.send(CollisionEvent::Pickup(entity_b));
                 }
             }
         }
 
     }
 
-    for(entity, _, mut collider, _, _, _, _) in query.iter_mut() {
+    for(entity, _, mut collider, _, _, _, _, _, _) in query.iter_mut() {
         collider.collisions = collisions.remove(&entity).unwrap_or_default();
     }
 }
 
-fn handle_collisions(
+pub fn handle_collisions(
     mut collision_reader: EventReader<CollisionEvent>,
-    entity_query: Query<&Collider, Without<Player>>,
-    mut player_query: Query<(&mut Collider, &mut Transform, &mut Health), With<Player>>,
-    transform_query: Query<&Transform, Without<Player>>,
+    entity_query: Query<Hitbox, Without<Player>>,
+    mut player_query: Query<(Entity, &mut Collider, &mut Transform, Has<Invulnerability>), With<Player>>,
+    transform_query: Query<(&Transform, Option<&OnHit>), Without<Player>>,
     time: Res<Time>,
-    mut health: Query<&mut Health, Without<Player>>,
+    mut damage_events: EventWriter<DamageEvent>,
 ) {
     for event in collision_reader.read() {
         match event {
             CollisionEvent::Collision => {
-                if let Ok((mut player_collider, mut player_transform, mut player_health)) = player_query.get_single_mut() {
-                    player_health.take_damage(10);
+                if let Ok((player_entity, mut player_collider, mut player_transform, invulnerable)) = player_query.get_single_mut() {
+                    // No damage or knockback while the player has i-frames
+                    if invulnerable {
+                        continue;
+                    }
+
                     let mut direction_vector = Vec3::ZERO;
+                    let mut effects = vec![];
 
                     for collision in player_collider.collisions.iter() {
-                        let enemy_transform = transform_query.get(*collision).expect("Collided with entity without collider");
+                        let (enemy_transform, on_hit) = transform_query.get(*collision).expect("Collided with entity without collider");
 
                         let direction = (player_transform.translation - enemy_transform.translation).normalize();
 
                         direction_vector += direction;
+
+                        if let Some(on_hit) = on_hit {
+                            effects.extend(on_hit.effects.iter().cloned());
+                        }
                     }
 
+                    damage_events.send(DamageEvent {
+                        target: player_entity,
+                        source: player_collider.collisions.first().copied(),
+                        ability: None,
+                        amount: 10,
+                        kind: DamageKind::Physical,
+                        effects,
+                    });
+
                     player_collider.collisions.clear();
 
                     player_transform.translation += direction_vector * time.delta_seconds() * ENEMY_SPEED;
@@ -83,15 +221,109 @@ fn handle_collisions(
             }
             CollisionEvent::Damage(entity) => {
                 info!("Damage collision detected");
-                if let Ok(entity_collider) = entity_query.get(*entity) {
+                if let Ok((entity_collider, on_hit, owner, ability_source)) = entity_query.get(*entity) {
                     for collisions in entity_collider.collisions.iter() {
-                        if let Ok(mut other_entity_health) = health.get_mut(*collisions) {
-                            other_entity_health.take_damage(1);
-                        }
+                        damage_events.send(DamageEvent {
+                            target: *collisions,
+                            source: owner.map(|owner| owner.0),
+                            ability: ability_source.map(|source| source.0),
+                            amount: 1,
+                            kind: DamageKind::Physical,
+                            effects: on_hit.map(|on_hit| on_hit.effects.clone()).unwrap_or_default(),
+                        });
                     }
                 }
                
             }
+            CollisionEvent::Pickup(_) => {}
         }    
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::components::{AppState, Health};
+    use crate::damage::DamagePlugin;
+    use crate::events::{DamageApplied, EntityDied};
+
+    fn wall() -> Rect {
+        Rect::new(0., 0., 100., 100.)
+    }
+
+    #[test]
+    fn flush_against_a_wall_dashing_away_is_not_a_hit() {
+        let start = Vec2::new(100., 50.);
+        assert_eq!(segment_entry(start, start + Vec2::new(40., 0.), wall()), None);
+    }
+
+    #[test]
+    fn flush_against_a_wall_sliding_along_it_is_not_a_hit() {
+        let start = Vec2::new(100., 50.);
+        assert_eq!(segment_entry(start, start + Vec2::new(0., 40.), wall()), None);
+    }
+
+    #[test]
+    fn flush_against_a_wall_dashing_into_it_is_a_hit() {
+        let start = Vec2::new(100., 50.);
+        assert_eq!(segment_entry(start, start + Vec2::new(-40., 0.), wall()), Some(0.));
+    }
+
+    #[test]
+    fn entry_point_is_a_fraction_of_the_segment() {
+        let start = Vec2::new(150., 50.);
+        assert_eq!(segment_entry(start, Vec2::new(50., 50.), wall()), Some(0.5));
+    }
+
+    // An enemy resting on the player, with the run in the given phase
+    fn enemy_touching_player(run_state: RunState) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .init_state::<AppState>()
+            .add_sub_state::<RunState>()
+            .add_event::<CollisionEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageApplied>()
+            .add_event::<EntityDied>()
+            .add_plugins((CollisionPlugin, DamagePlugin));
+
+        let player = app.world_mut().spawn((
+                Player,
+                Health { hp: 100 },
+                Collider::new(Vec2::splat(50.)),
+                Transform::default(),
+        )).id();
+        app.world_mut().spawn((
+                Enemy,
+                Collider::new(Vec2::splat(50.)),
+                Transform::from_xyz(10., 0., 0.),
+        ));
+
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::InRun);
+        app.update();
+        app.world_mut().resource_mut::<NextState<RunState>>().set(run_state);
+        app.update();
+
+        (app, player)
+    }
+
+    #[test]
+    fn touching_an_enemy_while_playing_hurts() {
+        let (mut app, player) = enemy_touching_player(RunState::Playing);
+        app.update();
+
+        assert!(app.world().get::<Health>(player).unwrap().hp < 100);
+    }
+
+    #[test]
+    fn touching_an_enemy_while_paused_does_no_damage() {
+        let (mut app, player) = enemy_touching_player(RunState::Paused);
+        for _ in 0..5 {
+            app.update();
+        }
+
+        assert_eq!(app.world().get::<Health>(player).unwrap().hp, 100);
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::components::{AppState, Combo, ComboBar, ComboText, ComboUi, GameUI, Player, RunState, UiTheme};
+use crate::events::DamageApplied;
+use crate::run::RunScopeAppExt;
+
+const BAR_WIDTH: f32 = 220.;
+const BAR_HEIGHT: f32 = 8.;
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<Combo>()
+            .add_systems(OnEnter(AppState::InRun), spawn_combo_ui)
+            .add_systems(Update, (
+                    break_combo_on_hit,
+                    decay_combo,
+                    update_combo_ui,
+            ).chain().run_if(in_state(RunState::Playing)));
+    }
+}
+
+fn break_combo_on_hit(
+    mut applied_events: EventReader<DamageApplied>,
+    player_query: Query<(), With<Player>>,
+    mut combo: ResMut<Combo>,
+) {
+    for event in applied_events.read() {
+        if event.amount > 0 && player_query.contains(event.target) {
+            combo.reset();
+        }
+    }
+}
+
+fn decay_combo(
+    time: Res<Time>,
+    mut combo: ResMut<Combo>,
+) {
+    if combo.count == 0 {
+        return;
+    }
+
+    if combo.timer.tick(time.delta()).just_finished() {
+        combo.reset();
+    }
+}
+
+fn spawn_combo_ui(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+) {
+    commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // Under the boss bar
+                    top: Val::Px(70.),
+                    width: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            ComboUi,
+            GameUI,
+            StateScoped(AppState::InRun),
+    ))
+        .with_children(|parent| {
+            parent.spawn((
+                    TextBundle::from_section("", theme.text_style(36.)),
+                    ComboText,
+            ));
+
+            // Decay bar, the fill shrinks as the window runs out
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(BAR_HEIGHT),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.5).into(),
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.),
+                                height: Val::Percent(100.),
+                                ..Default::default()
+                            },
+                            background_color: Color::srgb(1.0, 0.75, 0.1).into(),
+                            ..Default::default()
+                        },
+                        ComboBar,
+                ));
+            });
+        });
+}
+
+fn update_combo_ui(
+    combo: Res<Combo>,
+    mut root_query: Query<&mut Visibility, With<ComboUi>>,
+    mut text_query: Query<&mut Text, With<ComboText>>,
+    mut bar_query: Query<&mut Style, With<ComboBar>>,
+) {
+    for mut visibility in root_query.iter_mut() {
+        visibility.set_if_neq(if combo.count > 0 { Visibility::Inherited } else { Visibility::Hidden });
+    }
+
+    if combo.count == 0 {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("{} Combo  x{}", combo.count, combo.multiplier());
+    }
+
+    for mut style in bar_query.iter_mut() {
+        style.width = Val::Percent(100. * (1. - combo.timer.fraction()));
+    }
+}
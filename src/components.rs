@@ -1,6 +1,6 @@
-use bevy::{asset::Handle, ecs::entity::Entity, prelude::{Component, Resource, Timer, TimerMode, Vec2}, render::texture::Image, state::state::States, utils::HashSet};
+use bevy::{asset::Handle, color::Color, ecs::entity::Entity, prelude::{Component, Or, Rect, Resource, Timer, TimerMode, Vec2, With}, render::texture::Image, sprite::TextureAtlasLayout, text::Font, state::state::{StateSet, States, SubStates}, utils::HashSet};
 use std::{collections::HashMap, fmt, time::Duration};
-use crate::death_sound;
+use crate::{MAP_HEIGHT, MAP_WIDTH};
 
 // Common Components
 #[derive(Component)]
@@ -24,6 +24,28 @@ pub struct Health {
     pub hp: i32
 }
 
+// What Health bars are measured against
+#[derive(Component)]
+pub struct MaxHealth(pub i32);
+
+// Opt-in world space bar for anything with Health and MaxHealth, shown once it takes damage
+#[derive(Component)]
+pub struct HealthBar {
+    pub width: f32,
+    // Height above the entity's center
+    pub offset: f32,
+    // The bar while it's showing
+    pub bar: Option<Entity>,
+}
+
+#[derive(Component)]
+pub struct WorldHealthBar {
+    pub target: Entity,
+    pub fill: Entity,
+    // Restarts on every hit, the bar fades out near the end and goes away when it finishes
+    pub fade: Timer,
+}
+
 impl Health {
     pub fn take_damage(&mut self, amount: i32) {
         self.hp -= amount;
@@ -31,13 +53,166 @@ impl Health {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageKind {
+    Physical,
+    Fire,
+    Bleed,
+}
+
+// Flat reduction applied to physical hits
+#[derive(Component)]
+pub struct Armor(pub i32);
+
+// Fraction of each damage kind that is ignored
+#[derive(Component, Default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub fire: f32,
+    pub bleed: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Physical => self.physical,
+            DamageKind::Fire => self.fire,
+            DamageKind::Bleed => self.bleed,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Crit {
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+// The entity whose stats apply to a spawned hitbox or projectile
+#[derive(Component, Clone, Copy)]
+pub struct Owner(pub Entity);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatusKind {
+    Burn,
+    Bleed,
+    Slow,
+    Stun,
+    Vulnerable,
+}
+
+#[derive(Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub magnitude: f32, // damage per tick, slow fraction or extra damage taken
+    pub timer: Timer,
+    pub tick: Timer,
+    // Who applied it, filled in when the hit lands
+    pub source: Option<Entity>,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, magnitude: f32, duration: f32) -> Self {
+        Self {
+            kind,
+            magnitude,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+            tick: Timer::from_seconds(0.5, TimerMode::Repeating),
+            source: None,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    // One instance per kind and source, hitting again refreshes it instead of stacking another
+    pub fn add(&mut self, effect: StatusEffect) {
+        let existing = self.effects
+            .iter_mut()
+            .find(|existing| existing.kind == effect.kind && existing.source == effect.source);
+
+        match existing {
+            Some(existing) => {
+                existing.magnitude = existing.magnitude.max(effect.magnitude);
+                // The tick timer carries on so a refresh doesn't delay the next tick
+                if effect.timer.remaining() > existing.timer.remaining() {
+                    existing.timer = effect.timer;
+                }
+            }
+            None => self.effects.push(effect),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.has(StatusKind::Stun)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_stunned() {
+            return 0.0;
+        }
+
+        // Slows stack multiplicatively so they never reach a full stop
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == StatusKind::Slow)
+            .fold(1.0, |speed, effect| speed * (1.0 - effect.magnitude.clamp(0.0, 0.9)))
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        1.0 + self.effects
+            .iter()
+            .filter(|effect| effect.kind == StatusKind::Vulnerable)
+            .map(|effect| effect.magnitude)
+            .sum::<f32>()
+    }
+
+    pub fn tint(&self) -> Option<Color> {
+        // Strongest effect wins when several are active
+        [
+            (StatusKind::Stun, Color::srgb(1.0, 1.0, 0.3)),
+            (StatusKind::Burn, Color::srgb(1.0, 0.5, 0.1)),
+            (StatusKind::Bleed, Color::srgb(0.7, 0.0, 0.0)),
+            (StatusKind::Slow, Color::srgb(0.4, 0.7, 1.0)),
+            (StatusKind::Vulnerable, Color::srgb(0.7, 0.3, 0.9)),
+        ]
+            .into_iter()
+            .find(|(kind, _)| self.has(*kind))
+            .map(|(_, color)| color)
+    }
+}
+
+// Status effects applied to whatever this hitbox or enemy damages
+#[derive(Component, Clone, Default)]
+pub struct OnHit {
+    pub effects: Vec<StatusEffect>,
+}
+
 #[derive(Component)]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
 }
+// Ties a HUD slot's pieces to the ability they show
+#[derive(Component)]
+pub struct AbilitySlot(pub Ability);
+
+#[derive(Component)]
+pub struct CooldownRadial;
+
+#[derive(Component)]
+pub struct CooldownText;
+
 #[derive(Component)]
-pub struct CooldownUi;
+pub struct ChargesText;
 
 
 // Player Components
@@ -47,6 +222,79 @@ pub struct Line;
 #[derive(Component)]
 pub struct Player;
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum RangedKind {
+    Beam,
+    Projectile,
+}
+
+// The player's ranged loadout, upgrades change these values
+#[derive(Component)]
+pub struct RangedAttack {
+    pub kind: RangedKind,
+    pub multishot: u32,
+    pub spread: f32,
+    pub boomerang: bool,
+    pub speed: f32,
+    pub damage: i32,
+    pub pierce: u32,
+    pub falloff: f32,
+    pub max_range: f32,
+    pub blocked_by_obstacles: bool,
+    pub effects: Vec<StatusEffect>,
+}
+
+impl RangedAttack {
+    pub fn new() -> Self {
+        Self {
+            kind: RangedKind::Projectile,
+            multishot: 1,
+            spread: 0.5, // radians between the outermost shots
+            boomerang: false,
+            speed: 900.0,
+            damage: 2,
+            pierce: 2,
+            falloff: 0.5, // damage multiplier applied per enemy pierced
+            max_range: 1100.0,
+            blocked_by_obstacles: true,
+            effects: vec![StatusEffect::new(StatusKind::Burn, 1.0, 2.0)],
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    pub velocity: Vec2,
+    pub damage: i32,
+    pub falloff: f32,
+    pub pierce: u32,
+    pub travelled: f32,
+    pub max_range: f32,
+    pub boomerang: bool,
+    pub returning: bool,
+    pub blocked_by_obstacles: bool,
+    pub hits: Vec<Entity>,
+    pub effects: Vec<StatusEffect>,
+}
+
+// Extra lives left before a death ends the run
+#[derive(Component)]
+pub struct Lives(pub u32);
+
+// Present on the player between dying and either reviving or game over
+#[derive(Component)]
+pub struct Dying {
+    pub timer: Timer,
+}
+
+// Present on the player while a dash is carrying it
+#[derive(Component)]
+pub struct Dash {
+    pub direction: Vec2,
+    pub remaining: f32,
+    pub hits: Vec<Entity>,
+}
+
 #[derive(Resource)]
 pub struct SpawnTimer {
     pub timer: Timer,
@@ -130,6 +378,24 @@ pub struct wallpaper;
 #[derive(Component)]
 pub struct PointMarker;
 
+// World-space text such as damage numbers, drifts while its Lifetime runs out
+#[derive(Component)]
+pub struct FloatingText {
+    pub velocity: Vec2,
+}
+
+// Overrides the sprite tint for a moment after taking damage
+#[derive(Component)]
+pub struct HitFlash {
+    pub timer: Timer,
+}
+
+// Slows virtual time for a moment after heavy hits
+#[derive(Resource)]
+pub struct HitStop {
+    pub timer: Timer,
+}
+
 #[derive(Component)]
 pub struct Lifetime {
     pub timer: Timer,
@@ -139,6 +405,51 @@ pub struct Lifetime {
 #[derive(Component)]
 pub struct Enemy;
 
+// Everything the player's attacks can hurt
+pub type Attackable = Or<(With<Enemy>, With<Bigfoot>)>;
+
+// Base points for a kill, before the combo multiplier
+#[derive(Component)]
+pub struct PointValue(pub u32);
+
+// Loot components
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PickupKind {
+    Xp(u32),
+    Health(i32),
+    CooldownRefresh,
+    Magnet,
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub attracted: bool,
+}
+
+// Each entry is rolled independently when the owner dies
+#[derive(Component)]
+pub struct DropTable {
+    pub entries: Vec<(PickupKind, f32)>,
+}
+
+impl DropTable {
+    pub fn oni() -> Self {
+        Self {
+            entries: vec![
+                (PickupKind::Xp(1), 1.0),
+                (PickupKind::Health(25), 0.05),
+                (PickupKind::CooldownRefresh, 0.02),
+                (PickupKind::Magnet, 0.01),
+            ],
+        }
+    }
+}
+
+// Pickups inside this radius fly towards the player
+#[derive(Component)]
+pub struct PickupRadius(pub f32);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
 pub enum Ability {
     Dash,
@@ -146,6 +457,11 @@ pub enum Ability {
     Ranged,
     Aoe,
 }
+
+impl Ability {
+    // Fixed order so the HUD slots, the stats table and the export always list abilities the same way
+    pub const ALL: [Ability; 4] = [Ability::Attack, Ability::Ranged, Ability::Dash, Ability::Aoe];
+}
 impl fmt::Display for Ability {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -157,9 +473,21 @@ impl fmt::Display for Ability {
     }
 }
 
+// Which ability spawned a damaging entity, so its hits and kills can be credited to it
+#[derive(Clone, Copy, Component)]
+pub struct AbilitySource(pub Ability);
+
+// An ability with charges can be used until they run out, its cooldown brings them back one at a time
+#[derive(Clone, Copy)]
+pub struct Charges {
+    pub current: u32,
+    pub max: u32,
+}
+
 #[derive(Component)]
 pub struct Cooldowns {
     pub cooldowns: HashMap<Ability, Timer>,
+    pub charges: HashMap<Ability, Charges>,
 }
 
 impl Cooldowns {
@@ -169,10 +497,38 @@ impl Cooldowns {
         cooldowns.insert(Ability::Ranged, Timer::from_seconds(3.0, TimerMode::Once));
         cooldowns.insert(Ability::Attack, Timer::from_seconds(1.0, TimerMode::Once));    // 3 second cooldown
         cooldowns.insert(Ability::Aoe, Timer::from_seconds(10.0, TimerMode::Once)); // 10 second cooldown
-        Self { cooldowns }
+
+        let mut charges = HashMap::new();
+        charges.insert(Ability::Dash, Charges { current: 2, max: 2 });
+
+        Self { cooldowns, charges }
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        for (ability, timer) in self.cooldowns.iter_mut() {
+            timer.tick(delta);
+
+            if let Some(charges) = self.charges.get_mut(ability) {
+                if timer.just_finished() && charges.current < charges.max {
+                    charges.current += 1;
+                    // Keep recharging until full
+                    if charges.current < charges.max {
+                        timer.reset();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn charges(&self, ability: Ability) -> Option<Charges> {
+        self.charges.get(&ability).copied()
     }
 
     pub fn is_ready(&self, ability: Ability) -> bool {
+        if let Some(charges) = self.charges.get(&ability) {
+            return charges.current > 0;
+        }
+
         if let Some(timer) = self.cooldowns.get(&ability) {
             timer.finished()
         } else {
@@ -181,6 +537,15 @@ impl Cooldowns {
     }
 
     pub fn reset(&mut self, ability: Ability) {
+        if let Some(charges) = self.charges.get_mut(&ability) {
+            charges.current = charges.current.saturating_sub(1);
+            // A charge that's already recharging keeps going
+            if let Some(timer) = self.cooldowns.get_mut(&ability).filter(|timer| timer.finished()) {
+                timer.reset();
+            }
+            return;
+        }
+
         if let Some(timer) = self.cooldowns.get_mut(&ability) {
             timer.reset();
         }
@@ -195,29 +560,321 @@ impl Cooldowns {
         }
     }
 
-    pub fn reset_all(&mut self) {
+    // 1 right after use, 0 once the ability is ready again
+    pub fn remaining_fraction(&self, ability: Ability) -> f32 {
+        self.cooldowns.get(&ability).map_or(0., |timer| 1. - timer.fraction())
+    }
+
+    // Makes every ability ready to use right away
+    pub fn refresh_all(&mut self) {
         for timer in self.cooldowns.values_mut() {
-            timer.reset();
+            let duration = timer.duration();
+            timer.set_elapsed(duration);
+        }
+        for charges in self.charges.values_mut() {
+            charges.current = charges.max;
         }
     }
     
 }
 
+// Ability presses sampled in Update and consumed in FixedUpdate
+#[derive(Resource)]
+pub struct AbilityBuffer {
+    pub window: Duration,
+    pub presses: Vec<BufferedPress>,
+}
+
+pub struct BufferedPress {
+    pub ability: Ability,
+    pub timer: Timer,
+}
+
+impl AbilityBuffer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            presses: vec![],
+        }
+    }
+
+    pub fn push(&mut self, ability: Ability) {
+        // Pressing an ability that is already queued only refreshes its window
+        if let Some(press) = self.presses.iter_mut().find(|press| press.ability == ability) {
+            press.timer.reset();
+        } else {
+            self.presses.push(BufferedPress {
+                ability,
+                timer: Timer::new(self.window, TimerMode::Once),
+            });
+        }
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        for press in self.presses.iter_mut() {
+            press.timer.tick(delta);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.presses.clear();
+    }
+}
+
+#[derive(Component)]
+pub struct CameraController {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub frequency: f32, // spring stiffness, higher follows tighter
+    pub look_ahead: f32, // fraction of the cursor offset to lead by
+    pub max_look_ahead: f32,
+    pub trauma: f32,
+    pub trauma_decay: f32, // trauma lost per second
+    pub max_shake: f32,
+    pub max_roll: f32,
+    pub bounds: Option<Rect>, // None for the endless map
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    pub zoom_speed: f32,
+    pub density_radius: f32,
+    pub density_for_max_zoom: f32,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            frequency: 8.0,
+            look_ahead: 0.2,
+            max_look_ahead: 150.0,
+            trauma: 0.0,
+            trauma_decay: 1.5,
+            max_shake: 25.0,
+            max_roll: 0.03,
+            bounds: None,
+            min_zoom: 1.0,
+            max_zoom: 1.5,
+            zoom_speed: 1.0,
+            density_radius: 700.0,
+            density_for_max_zoom: 60.0,
+        }
+    }
+}
+
+impl CameraController {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
 #[derive(Component)]
 pub struct Map;
 
+// The cell a chunk backdrop sits in and which template it was rolled from
+#[derive(Component)]
+pub struct Chunk {
+    pub cell: (i32, i32),
+    pub template: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PropKind {
+    Grass,
+    Rock,
+    Wall,
+    Shrine,
+}
+
+impl PropKind {
+    pub fn size(&self) -> Vec2 {
+        match self {
+            PropKind::Grass => Vec2::new(24., 24.),
+            PropKind::Rock => Vec2::new(90., 80.),
+            PropKind::Wall => Vec2::new(320., 60.),
+            PropKind::Shrine => Vec2::new(140., 140.),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PropKind::Grass => Color::srgb(0.35, 0.6, 0.25),
+            PropKind::Rock => Color::srgb(0.45, 0.45, 0.45),
+            PropKind::Wall => Color::srgb(0.4, 0.28, 0.18),
+            PropKind::Shrine => Color::srgb(0.75, 0.15, 0.15),
+        }
+    }
+
+    // Decorations are walked over, everything else is terrain
+    pub fn is_obstacle(&self) -> bool {
+        !matches!(self, PropKind::Grass)
+    }
+}
+
+// Its look and collider are fixed by its PropKind when it spawns
+#[derive(Component)]
+pub struct Prop;
+
+// World-space box that blocks movement, never moves once spawned
+#[derive(Component)]
+pub struct StaticCollider {
+    pub rect: Rect,
+}
+
+// Spatial hash over every StaticCollider, rebuilt when obstacles come and go
+#[derive(Default, Resource)]
+pub struct ObstacleGrid {
+    pub cells: HashMap<(i32, i32), Vec<Rect>>,
+}
+
+impl ObstacleGrid {
+    pub const CELL_SIZE: f32 = 256.;
+
+    pub fn cell_of(position: Vec2) -> (i32, i32) {
+        (
+            (position.x / Self::CELL_SIZE).floor() as i32,
+            (position.y / Self::CELL_SIZE).floor() as i32,
+        )
+    }
+
+    pub fn insert(&mut self, rect: Rect) {
+        let (min_x, min_y) = Self::cell_of(rect.min);
+        let (max_x, max_y) = Self::cell_of(rect.max);
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.cells.entry((x, y)).or_default().push(rect);
+            }
+        }
+    }
+
+    // Every obstacle in the cells touched by area, big obstacles may repeat
+    pub fn query(&self, area: Rect) -> impl Iterator<Item = &Rect> {
+        let (min_x, min_y) = Self::cell_of(area.min);
+        let (max_x, max_y) = Self::cell_of(area.max);
+
+        (min_x..=max_x)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |rect| !rect.intersect(area).is_empty())
+    }
+}
+
+pub struct ChunkTemplate {
+    pub weight: u32,
+    pub tint: Color,
+    pub props: Vec<(PropKind, u32)>, // kind and how many to scatter
+}
+
+#[derive(Resource)]
+pub struct ChunkTemplates(pub Vec<ChunkTemplate>);
+
+impl Default for ChunkTemplates {
+    fn default() -> Self {
+        Self(vec![
+            // Meadow
+            ChunkTemplate {
+                weight: 5,
+                tint: Color::WHITE,
+                props: vec![(PropKind::Grass, 60), (PropKind::Rock, 6)],
+            },
+            // Rocky field
+            ChunkTemplate {
+                weight: 3,
+                tint: Color::srgb(0.85, 0.85, 0.8),
+                props: vec![(PropKind::Grass, 20), (PropKind::Rock, 25)],
+            },
+            // Ruins
+            ChunkTemplate {
+                weight: 2,
+                tint: Color::srgb(0.8, 0.75, 0.7),
+                props: vec![(PropKind::Grass, 30), (PropKind::Wall, 10), (PropKind::Rock, 8)],
+            },
+            // Shrine grove
+            ChunkTemplate {
+                weight: 1,
+                tint: Color::srgb(0.9, 0.8, 0.85),
+                props: vec![(PropKind::Grass, 80), (PropKind::Shrine, 2)],
+            },
+        ])
+    }
+}
+
+// Seed for the map generator, the same seed rebuilds the same cells
+#[derive(Resource)]
+pub struct MapSeed(pub u64);
+
+// Every run rolls a fresh layout
+impl Default for MapSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+// Owns every loaded map chunk, keyed by grid cell
 #[derive(Default, Resource)]
 pub struct MapGrid {
-    pub positions: HashSet<(i32, i32)>, // A set to track the positions of maps on the grid
+    pub chunks: HashMap<(i32, i32), Entity>,
+}
+
+impl MapGrid {
+    pub fn cell_of(position: Vec2) -> (i32, i32) {
+        (
+            (position.x / MAP_WIDTH).round() as i32,
+            (position.y / MAP_HEIGHT).round() as i32,
+        )
+    }
+
+    pub fn cell_center(cell: (i32, i32)) -> Vec2 {
+        Vec2::new(cell.0 as f32 * MAP_WIDTH, cell.1 as f32 * MAP_HEIGHT)
+    }
+}
+
+// Every chunk cell loaded at some point during the run
+#[derive(Default, Resource)]
+pub struct ExploredChunks(pub HashSet<(i32, i32)>);
+
+// Texture the minimap is drawn into, and whether the player wants it shown
+#[derive(Resource)]
+pub struct Minimap {
+    pub image: Handle<Image>,
+    pub visible: bool,
+}
+
+#[derive(Component)]
+pub struct MinimapUi;
+
+// Shows an arrow at the screen edge while the entity is off camera, wind_up runs 0 to 1 before an attack
+#[derive(Component)]
+pub struct TrackedThreat {
+    pub color: Color,
+    pub wind_up: f32,
+}
+
+impl TrackedThreat {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            wind_up: 0.,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ThreatIndicator {
+    pub target: Entity,
 }
 
+#[derive(Resource)]
+pub struct ThreatArrow(pub Handle<Image>);
+
 #[derive(Component)]
 pub struct Bigfoot {
     pub x: f32,
     pub y: f32,
     pub state: BigfootState,
     pub timer: Timer,
-    pub health: i32,
     pub airTexture: Handle<Image>,
     pub groundTexture: Handle<Image>,
 }
@@ -229,22 +886,10 @@ impl Bigfoot {
             state: BigfootState::Invulnerable,
             x,
             y,
-            health: 5,
             airTexture: todo!(),
-            groundTexture: todo!(), // Initial health value
-        }
-    }
-
-    pub fn take_damage(&mut self, amount: i32) {
-        self.health -= amount;
-        if self.health < 0 {
-            self.health = 0;
+            groundTexture: todo!(),
         }
     }
-
-    pub fn is_dead(&self) -> bool {
-        self.health <= 0
-    }
 }
 
 #[derive(PartialEq)]
@@ -258,70 +903,272 @@ pub enum BigfootState {
 pub struct GameOverUI;
 
 
+// Owned by the current run, despawned when the next run starts
 #[derive(Component)]
-pub struct Resettable;
+pub struct RunScope;
 
 #[derive(Component)]
 pub struct GameUI;
 
 
-#[derive(Resource)]
+#[derive(Default, Resource)]
 pub struct Score {
     pub enemies_killed: u32,
+    // Kills weighted by enemy type and combo multiplier
+    pub points: u32,
+    pub xp: u32,
+    pub level: u32,
 }
 
-#[derive(Resource)]
+#[derive(Default, Resource)]
 pub struct GameTimer(pub f32);
 
+// Kills in quick succession build up a score multiplier, a hit on the player breaks it
+#[derive(Resource)]
+pub struct Combo {
+    pub count: u32,
+    pub best: u32,
+    // Runs down between kills, the combo is lost when it finishes
+    pub timer: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            count: 0,
+            best: 0,
+            timer: Timer::from_seconds(Self::WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+impl Combo {
+    pub const WINDOW: f32 = 3.0;
+    pub const KILLS_PER_STEP: u32 = 5;
+    pub const MAX_MULTIPLIER: u32 = 8;
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.count / Self::KILLS_PER_STEP).min(Self::MAX_MULTIPLIER)
+    }
+
+    // Counts the kill and returns the multiplier it scores with
+    pub fn add_kill(&mut self) -> u32 {
+        self.count += 1;
+        self.best = self.best.max(self.count);
+        self.timer.reset();
+        self.multiplier()
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+#[derive(Component)]
+pub struct ComboUi;
+
+#[derive(Component)]
+pub struct ComboText;
+
+#[derive(Component)]
+pub struct ComboBar;
+
+// How long the boss intro holds the run before play starts
+#[derive(Resource)]
+pub struct IntroTimer(pub Timer);
+
+// Snapshot of the run taken when the player runs out of lives
+#[derive(Default, Resource)]
+pub struct RunSummary {
+    pub enemies_killed: u32,
+    pub points: u32,
+    pub time_survived: f32,
+    pub lives_used: u32,
+}
+
+// Everything worth reporting about the current run, filled in from gameplay events
+#[derive(Default, Resource)]
+pub struct RunStats {
+    pub kills: HashMap<Ability, u32>,
+    // Kills nothing can be credited for, like enemies bleeding out
+    pub other_kills: u32,
+    pub damage_dealt: i32,
+    pub damage_taken: i32,
+    pub dashes: u32,
+    pub distance: f32,
+    pub peak_enemies: u32,
+    pub bigfoot_damage: i32,
+    // Seconds spent in each wave, a wave being the stretch between two level ups
+    pub wave_times: Vec<f32>,
+    pub last_position: Option<Vec2>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    // Points with the combo multiplier applied
+    pub score: u32,
+    pub time_survived: f32,
+    pub won: bool,
+    pub seed: u64,
+    // Seconds since the unix epoch
+    pub date: u64,
+    pub version: String,
+}
+
+// Best runs on this machine, best first
+#[derive(Default, Resource)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+    // Where the run that just ended landed, None if it didn't make the table
+    pub last_rank: Option<usize>,
+}
+
 #[derive(Component)]
 pub struct GameTimerText;
 
 
 impl Score {
-    pub fn new() -> Self {
-        Score {
-            enemies_killed: 0,
-        }
+    // Total xp needed to reach the next level, each level costs 10 more than the last
+    pub fn next_level_xp(&self) -> u32 {
+        10 * (self.level + 1) * (self.level + 2) / 2
     }
 
-    pub fn reset(&mut self) {
-        self.enemies_killed = 0;
+    pub fn add_xp(&mut self, amount: u32) {
+        self.xp += amount;
     }
 
-    pub fn increment(&mut self) {
+    pub fn add_kill(&mut self, points: u32) {
         self.enemies_killed += 1;
+        self.points += points;
     }
 
     pub fn get_enemies_killed(&self) -> u32 {
         self.enemies_killed
     }
 }
+// Which screen the game is on, a run happens entirely inside InRun
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
-pub enum GameState {
+pub enum AppState {
     #[default]
-    Running,
     Menu,
-    Paused,
-    Reset,
+    InRun,
     GameOver,
-    Won
+    Won,
+    // Passes straight back into InRun, so a run can be restarted from inside itself
+    Restarting,
 }
 
-#[derive(Resource)]
-pub struct CurrentGameState {
-    pub(crate) state: GameState,
+// Phases within a run, every run opens on the boss intro
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, SubStates)]
+#[source(AppState = AppState::InRun)]
+pub enum RunState {
+    #[default]
+    BossIntro,
+    Playing,
+    Paused,
+    LevelUp,
 }
 #[derive(Component)]
 pub struct PauseMenu;
 
-#[derive(Component, PartialEq)]
-pub struct StartButton;
+// What a menu button does once it's clicked or confirmed with the keyboard or a gamepad
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonAction {
+    Start,
+    Quit,
+    Resume,
+    Restart,
+    MainMenu,
+    OpenSettings,
+    CloseSettings,
+    ChangeSetting(SettingField),
+    ExportStats,
+}
+
+#[derive(Component)]
+pub struct MenuButton {
+    pub action: ButtonAction,
+    pub color: Color,
+}
+
+// Whether the settings screen is open on top of the main or pause menu
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum SettingsMenu {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingField {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    Resolution,
+    ScreenShake,
+    DamageNumbers,
+}
+
+impl SettingField {
+    pub const ALL: [SettingField; 7] = [
+        SettingField::MasterVolume,
+        SettingField::MusicVolume,
+        SettingField::SfxVolume,
+        SettingField::Fullscreen,
+        SettingField::Resolution,
+        SettingField::ScreenShake,
+        SettingField::DamageNumbers,
+    ];
+}
+
+// Player options, loaded from and saved to the config file
+#[derive(Clone, Debug, PartialEq, Resource)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    pub resolution: (u32, u32),
+    pub screen_shake: bool,
+    pub damage_numbers: bool,
+}
 
-#[derive(Component, PartialEq)]
-pub struct RestartButton;
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            fullscreen: false,
+            resolution: (1280, 720),
+            screen_shake: true,
+            damage_numbers: true,
+        }
+    }
+}
 
+// Marks the looping background track so its volume can follow the settings
 #[derive(Component)]
-pub struct QuitButton;
+pub struct BackgroundMusic;
+
+// The button keyboard and gamepad navigation acts on, also follows the mouse
+#[derive(Default, Resource, PartialEq)]
+pub struct UiFocus(pub Option<Entity>);
+
+// Shared look for every menu, the font is loaded once and reused
+#[derive(Resource)]
+pub struct UiTheme {
+    pub font: Handle<Font>,
+    pub text_color: Color,
+    pub button_color: Color,
+    pub confirm_color: Color,
+    pub danger_color: Color,
+    // How much lighter a button gets while focused or hovered
+    pub focus_lightness: f32,
+    pub button_size: Vec2,
+    pub button_font_size: f32,
+}
 
 
 #[derive(Component)]
@@ -342,6 +1189,26 @@ pub struct Points(pub Vec<Vec2>);
 #[derive(Component)]
 pub struct HealthText;
 
+#[derive(Component)]
+pub struct HealthBarFill;
+
+// Index of the extra life this pip stands for
+#[derive(Component)]
+pub struct LifePip(pub u32);
+
+#[derive(Component)]
+pub struct BossBar;
+
+#[derive(Component)]
+pub struct BossBarFill;
+
+// Generated pie masks the cooldown slots step through
+#[derive(Resource)]
+pub struct RadialAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
 #[derive(Component)]
 pub struct ScoreText;
 
@@ -350,3 +1217,7 @@ pub struct ScoreText;
 pub struct Invulnerability {
     pub timer: Timer,
 }
+
+// Seconds of Invulnerability granted by every hit that lands
+#[derive(Component)]
+pub struct HitInvulnerability(pub f32);
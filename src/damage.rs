@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::Rng;
+
+use crate::collision::handle_collisions;
+use crate::components::{Armor, Crit, DamageKind, Health, HitInvulnerability, Invulnerability, Player, Resistances, RunState, StatusEffects};
+use crate::events::{DamageApplied, DamageEvent, EntityDied};
+
+// Everything a hit is checked against on its target
+type DamageTarget = (
+    &'static mut Health,
+    Option<&'static mut StatusEffects>,
+    Option<&'static Invulnerability>,
+    Option<&'static HitInvulnerability>,
+    Option<&'static Armor>,
+    Option<&'static Resistances>,
+    Has<Player>,
+);
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, resolve_damage.after(handle_collisions).run_if(in_state(RunState::Playing)));
+    }
+}
+
+// Turns raw DamageEvents into health changes, in this order:
+// invulnerability, crit, vulnerability, resistance, armor
+fn resolve_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut target_query: Query<DamageTarget>,
+    crit_query: Query<&Crit>,
+    mut applied_events: EventWriter<DamageApplied>,
+    mut died_events: EventWriter<EntityDied>,
+) {
+    let mut rng = rand::thread_rng();
+    // Invulnerability inserted below only lands once commands apply, so later hits in the
+    // same batch, like the rest of one swing's markers, are checked against this instead
+    let mut granted_invulnerability = HashSet::new();
+
+    for event in damage_events.read() {
+        let Ok((mut health, status_effects, invulnerability, hit_invulnerability, armor, resistances, is_player)) = target_query.get_mut(event.target) else {
+            continue;
+        };
+
+        // Already dead this frame or in i-frames, the hit is ignored entirely
+        if health.hp <= 0 || invulnerability.is_some() || granted_invulnerability.contains(&event.target) {
+            continue;
+        }
+
+        let mut amount = event.amount as f32;
+
+        let mut crit = false;
+        if let Some(source_crit) = event.source.and_then(|source| crit_query.get(source).ok()) {
+            if rng.gen::<f32>() < source_crit.chance {
+                crit = true;
+                amount *= source_crit.multiplier;
+            }
+        }
+
+        if let Some(status_effects) = &status_effects {
+            amount *= status_effects.damage_multiplier();
+        }
+
+        if let Some(resistances) = resistances {
+            amount *= 1.0 - resistances.get(event.kind).clamp(0.0, 1.0);
+        }
+
+        let mut amount = amount.round() as i32;
+
+        if let Some(armor) = armor {
+            if event.kind == DamageKind::Physical {
+                amount = (amount - armor.0).max(0);
+            }
+        }
+
+        health.take_damage(amount);
+
+        if let Some(hit_invulnerability) = hit_invulnerability {
+            commands.entity(event.target).insert(Invulnerability {
+                timer: Timer::from_seconds(hit_invulnerability.0, TimerMode::Once),
+            });
+            granted_invulnerability.insert(event.target);
+        }
+
+        let effects = event.effects.iter().cloned().map(|mut effect| {
+            effect.source = event.source;
+            effect
+        });
+
+        match status_effects {
+            Some(mut status_effects) => {
+                for effect in effects {
+                    status_effects.add(effect);
+                }
+            }
+            None if !event.effects.is_empty() => {
+                let mut status_effects = StatusEffects::default();
+                for effect in effects {
+                    status_effects.add(effect);
+                }
+                commands.entity(event.target).insert(status_effects);
+            }
+            None => {}
+        }
+
+        applied_events.send(DamageApplied {
+            target: event.target,
+            amount,
+            kind: event.kind,
+            crit,
+        });
+
+        if health.hp <= 0 {
+            died_events.send(EntityDied {
+                entity: event.target,
+                ability: event.ability,
+                is_player,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::components::AppState;
+
+    fn hit(target: Entity) -> DamageEvent {
+        DamageEvent {
+            target,
+            source: None,
+            ability: None,
+            amount: 1,
+            kind: DamageKind::Physical,
+            effects: vec![],
+        }
+    }
+
+    #[test]
+    fn hit_invulnerability_stops_the_rest_of_the_same_batch() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .init_state::<AppState>()
+            .add_sub_state::<RunState>()
+            .add_event::<DamageEvent>()
+            .add_event::<DamageApplied>()
+            .add_event::<EntityDied>()
+            .add_systems(Update, resolve_damage.run_if(in_state(RunState::Playing)));
+
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::InRun);
+        app.update();
+        app.world_mut().resource_mut::<NextState<RunState>>().set(RunState::Playing);
+        app.update();
+
+        let target = app.world_mut().spawn((Health { hp: 20 }, HitInvulnerability(0.25))).id();
+        // One swing's markers all overlapping the target on the same frame
+        app.world_mut().send_event_batch((0..12).map(|_| hit(target)));
+        app.update();
+
+        assert_eq!(app.world().get::<Health>(target).unwrap().hp, 19);
+        assert!(app.world().get::<Invulnerability>(target).is_some());
+    }
+}
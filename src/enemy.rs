@@ -3,14 +3,19 @@ use std::f32::consts::PI;
 use bevy::prelude::*;
 use rand::Rng;
 
-use crate::{components::{Collider, Enemy, GameState, Health, Player, Velocity}, EnemySpawnRate, GameTextures, ENEMY_SPEED, PLAYER_RADIUS, SPRITE_SCALE, SPRITE_SIZE};
+use crate::{components::{Collider, DropTable, Enemy, Health, HealthBar, MapGrid, MaxHealth, ObstacleGrid, OnHit, Player, PointValue, RunScope, RunState, StatusEffect, StatusEffects, StatusKind, Velocity}, flow_field::FlowField, run::RunScopeAppExt, EnemySpawnRate, GameTextures, ENEMY_SPEED, ONI_HEALTH, ONI_POINTS, MAP_HEIGHT, MAP_WIDTH, PLAYER_RADIUS, SPRITE_SCALE, SPRITE_SIZE};
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
    fn build(&self, app: &mut App) {
-       app.add_systems(Update, enemy_spawn_system.run_if(in_state(GameState::Running)))
-           .add_systems(FixedUpdate, (player_tracking_system, enemy_movement_system).run_if(in_state(GameState::Running)));
+       app.init_run_resource::<FlowField>()
+           .add_systems(Update, enemy_spawn_system.run_if(in_state(RunState::Playing)))
+           .add_systems(FixedUpdate, (
+                   update_flow_field,
+                   player_tracking_system.after(update_flow_field),
+                   enemy_movement_system.after(player_tracking_system),
+           ).run_if(in_state(RunState::Playing)));
    } 
 }
 
@@ -40,27 +45,75 @@ fn enemy_spawn_system(
                     ..Default::default()
                 },
                 Health {
-                    hp: 1,
+                    hp: ONI_HEALTH,
                 },
+                MaxHealth(ONI_HEALTH),
+                HealthBar::new(40., 30.),
                 Collider::new(Vec2::splat(SPRITE_SIZE.0 * SPRITE_SCALE)),
                 Enemy,
+                PointValue(ONI_POINTS),
+                // Oni claws slow whoever they catch
+                OnHit {
+                    effects: vec![StatusEffect::new(StatusKind::Slow, 0.3, 1.5)],
+                },
+                DropTable::oni(),
+                StatusEffects::default(),
                 Velocity {
                     x: 0.,
                     y: 0.,
                 },
+                RunScope,
         ));
         enemy_spawn_rate.0 -= 0.025;
     }
 }
 
 
+// Re-fits the field when chunks or obstacles change, otherwise only re-integrates when the player moves cell
+fn update_flow_field(
+    mut flow_field: ResMut<FlowField>,
+    map_grid: Res<MapGrid>,
+    obstacle_grid: Res<ObstacleGrid>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+
+    if map_grid.is_changed() || obstacle_grid.is_changed() {
+        let mut bounds = Rect::default();
+        for cell in map_grid.chunks.keys() {
+            let chunk = Rect::from_center_size(MapGrid::cell_center(*cell), Vec2::new(MAP_WIDTH, MAP_HEIGHT));
+            bounds = if bounds.is_empty() { chunk } else { bounds.union(chunk) };
+        }
+
+        flow_field.resize(bounds, obstacle_grid.cells.values().flatten());
+        flow_field.build(player_position);
+    } else if flow_field.target != Some(FlowField::cell_of(player_position)) {
+        flow_field.build(player_position);
+    }
+}
+
 fn player_tracking_system(
     player_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<(&mut Velocity, &Transform),With<Enemy>>,
+    mut enemy_query: Query<(&mut Velocity, &Transform, &StatusEffects),With<Enemy>>,
+    flow_field: Res<FlowField>,
 ) { 
     if let Ok(player_transform) = player_query.get_single() {
-        for (mut velocity, enemy_transform) in enemy_query.iter_mut() {
-            let direction_vector = (player_transform.translation - enemy_transform.translation).normalize();
+        for (mut velocity, enemy_transform, status_effects) in enemy_query.iter_mut() {
+            // Stunned enemies stop chasing the player
+            if status_effects.is_stunned() {
+                velocity.x = 0.;
+                velocity.y = 0.;
+                continue;
+            }
+
+            // Follow the flow field, and head straight at the player once in their cell or off the grid
+            let position = enemy_transform.translation.truncate();
+            let direction_vector = flow_field
+                .sample(position)
+                .unwrap_or_else(|| (player_transform.translation.truncate() - position).normalize_or_zero());
             velocity.x = direction_vector.x;
             velocity.y = direction_vector.y;
         }
@@ -69,13 +122,14 @@ fn player_tracking_system(
 }
 
 fn enemy_movement_system(
-    mut query: Query<(&Velocity, &mut Transform), With<Enemy>>,
+    mut query: Query<(&Velocity, &mut Transform, &StatusEffects), With<Enemy>>,
     time: Res<Time>,
 ) {
-    for (velocity, mut transform) in query.iter_mut() {
+    for (velocity, mut transform, status_effects) in query.iter_mut() {
         let translation = &mut transform.translation;
+        let speed = ENEMY_SPEED * status_effects.speed_multiplier();
 
-        translation.x += velocity.x * time.delta_seconds() * ENEMY_SPEED;
-        translation.y += velocity.y * time.delta_seconds() * ENEMY_SPEED;
+        translation.x += velocity.x * time.delta_seconds() * speed;
+        translation.y += velocity.y * time.delta_seconds() * speed;
     }
 }
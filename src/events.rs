@@ -1,8 +1,62 @@
 use bevy::prelude::*;
 
+use crate::components::{Ability, ButtonAction, DamageKind, StatusEffect};
+
 #[derive(Event)]
 pub enum CollisionEvent{
     Collision,
     Damage(Entity),
+    Pickup(Entity),
 } // Event carrying the entity to delete
 
+#[derive(Event)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub ability: Option<Ability>,
+    pub amount: i32,
+    pub kind: DamageKind,
+    pub effects: Vec<StatusEffect>,
+}
+
+// Sent once a DamageEvent made it through invulnerability, armor and resistances
+#[derive(Event)]
+pub struct DamageApplied {
+    pub target: Entity,
+    pub amount: i32,
+    pub kind: DamageKind,
+    pub crit: bool,
+}
+
+#[derive(Event)]
+pub struct EntityDied {
+    pub entity: Entity,
+    pub ability: Option<Ability>,
+    pub is_player: bool,
+}
+
+#[derive(Event)]
+pub struct PlayerDied;
+
+// Adds trauma to the camera, 1.0 is the strongest shake
+#[derive(Event)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+// Sent once a chunk backdrop exists, hook this to fill the chunk with content
+#[derive(Event)]
+pub struct ChunkLoaded {
+    pub cell: (i32, i32),
+    pub entity: Entity,
+}
+
+// Sent as a chunk is despawned, along with everything parented to it
+#[derive(Event)]
+pub struct ChunkUnloaded {
+    pub cell: (i32, i32),
+}
+
+// A menu button was activated with the mouse, keyboard or a gamepad
+#[derive(Event)]
+pub struct ButtonPressed(pub ButtonAction);
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::{DamageKind, Enemy, FloatingText, HitFlash, HitStop, Lifetime, Player, RunScope, RunState, Settings, UiTheme};
+use crate::events::DamageApplied;
+use crate::run::RunScopeAppExt;
+use crate::{bone_hit, enemy_ouch_sound, play_hit_swing, SoundEffects};
+
+const FLASH_DURATION: f32 = 0.1;
+const HIT_STOP_DURATION: f32 = 0.06;
+const HIT_STOP_SPEED: f32 = 0.05;
+
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<HitStop>()
+            .add_systems(Update, (spawn_hit_feedback, update_hit_stop).run_if(in_state(RunState::Playing)))
+            .add_systems(OnExit(RunState::Playing), end_hit_stop)
+            .add_systems(FixedUpdate, (float_damage_numbers, update_hit_flash).run_if(in_state(RunState::Playing)));
+    }
+}
+
+impl Default for HitStop {
+    // Starts out finished so a run does not open on a hit-stop
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(HIT_STOP_DURATION, TimerMode::Once);
+        timer.tick(Duration::from_secs_f32(HIT_STOP_DURATION));
+
+        HitStop { timer }
+    }
+}
+
+fn spawn_hit_feedback(
+    mut commands: Commands,
+    mut applied_events: EventReader<DamageApplied>,
+    victim_query: Query<(&Transform, Has<Player>, Has<Enemy>)>,
+    mut hit_stop: ResMut<HitStop>,
+    sfx: SoundEffects,
+    settings: Res<Settings>,
+    theme: Res<UiTheme>,
+) {
+    let mut rng = rand::thread_rng();
+    let mut enemy_hit = false;
+    let mut player_hit = false;
+
+    for event in applied_events.read() {
+        let Ok((transform, is_player, is_enemy)) = victim_query.get(event.target) else {
+            continue;
+        };
+
+        let position = transform.translation.truncate() + Vec2::new(rng.gen_range(-20.0..20.0), 30.);
+        let (color, font_size, label) = if event.crit {
+            (Color::srgb(1.0, 0.85, 0.1), 36.0, format!("{}!", event.amount))
+        } else if is_player {
+            (Color::srgb(1.0, 0.3, 0.3), 28.0, event.amount.to_string())
+        } else {
+            (kind_color(event.kind), 28.0, event.amount.to_string())
+        };
+
+        if settings.damage_numbers {
+            commands.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                                  label,
+                                  TextStyle {
+                                      color,
+                                      ..theme.text_style(font_size)
+                                  },
+                              ),
+                        transform: Transform::from_xyz(position.x, position.y, 20.),
+                        ..Default::default()
+                    },
+                    FloatingText {
+                        velocity: Vec2::new(0., 60.),
+                    },
+                    Lifetime {
+                        timer: Timer::from_seconds(0.8, TimerMode::Once),
+                    },
+                    RunScope,
+            ));
+        }
+
+        commands.entity(event.target).try_insert(HitFlash {
+            timer: Timer::from_seconds(FLASH_DURATION, TimerMode::Once),
+        });
+
+        // Only crits and hits on the player are worth freezing the game for
+        if event.crit || is_player {
+            hit_stop.timer.reset();
+        }
+
+        enemy_hit |= is_enemy;
+        player_hit |= is_player;
+    }
+
+    // One set of sounds per frame, no matter how many things got hit
+    if enemy_hit {
+        play_hit_swing(&sfx, &mut commands);
+        enemy_ouch_sound(&sfx, &mut commands);
+    }
+    if player_hit {
+        bone_hit(&sfx, &mut commands);
+    }
+}
+
+// Damage over time and elemental hits read differently from plain ones
+fn kind_color(kind: DamageKind) -> Color {
+    match kind {
+        DamageKind::Physical => Color::WHITE,
+        DamageKind::Fire => Color::srgb(1.0, 0.55, 0.1),
+        DamageKind::Bleed => Color::srgb(0.85, 0.1, 0.1),
+    }
+}
+
+fn update_hit_stop(
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    hit_stop.timer.tick(real_time.delta());
+
+    if hit_stop.timer.finished() {
+        virtual_time.set_relative_speed(1.0);
+    } else {
+        virtual_time.set_relative_speed(HIT_STOP_SPEED);
+    }
+}
+
+// A killing blow or Esc can leave Playing mid hit-stop, which would otherwise keep the world slowed
+fn end_hit_stop(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    let remaining = hit_stop.timer.remaining();
+    hit_stop.timer.tick(remaining);
+    virtual_time.set_relative_speed(1.0);
+}
+
+fn float_damage_numbers(
+    time: Res<Time>,
+    mut query: Query<(&FloatingText, &Lifetime, &mut Transform, &mut Text)>,
+) {
+    for (floating_text, lifetime, mut transform, mut text) in query.iter_mut() {
+        transform.translation += floating_text.velocity.extend(0.) * time.delta_seconds();
+
+        let alpha = 1.0 - lifetime.timer.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
+
+fn update_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    for (entity, mut hit_flash, mut sprite) in query.iter_mut() {
+        hit_flash.timer.tick(time.delta());
+
+        let alpha = sprite.color.alpha();
+        if hit_flash.timer.finished() {
+            sprite.color = Color::WHITE.with_alpha(alpha);
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            sprite.color = Color::srgb(1.0, 0.2, 0.2).with_alpha(alpha);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+// Marks a cell the integration pass never reached
+const UNREACHABLE: u32 = u32::MAX;
+
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+// Grid of directions leading every open cell towards a single target cell
+#[derive(Default, Resource)]
+pub struct FlowField {
+    pub origin: IVec2,
+    pub width: i32,
+    pub height: i32,
+    pub target: Option<IVec2>,
+    // Cells the last build integrated, everything outside it is unreachable
+    pub window: Option<IRect>,
+    pub blocked: Vec<bool>,
+    pub costs: Vec<u32>,
+    pub directions: Vec<Vec2>,
+}
+
+impl FlowField {
+    pub const CELL_SIZE: f32 = 128.;
+
+    // Builds only integrate this many cells around the target, so moving a cell costs the same
+    // however many chunks are loaded. Enemies further out head straight for the player
+    pub const WINDOW: i32 = 24;
+
+    pub fn cell_of(position: Vec2) -> IVec2 {
+        (position / Self::CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        let local = cell - self.origin;
+
+        if local.x < 0 || local.y < 0 || local.x >= self.width || local.y >= self.height {
+            None
+        } else {
+            Some((local.y * self.width + local.x) as usize)
+        }
+    }
+
+    fn is_open(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some_and(|index| !self.blocked[index])
+    }
+
+    // Fits the grid over bounds and marks every cell an obstacle touches, clears the old field
+    pub fn resize<'a>(&mut self, bounds: Rect, obstacles: impl Iterator<Item = &'a Rect>) {
+        let min = Self::cell_of(bounds.min);
+        let max = Self::cell_of(bounds.max);
+
+        self.origin = min;
+        self.width = max.x - min.x + 1;
+        self.height = max.y - min.y + 1;
+        self.target = None;
+        self.window = None;
+
+        let size = (self.width * self.height) as usize;
+        self.blocked = vec![false; size];
+        self.costs = vec![UNREACHABLE; size];
+        self.directions = vec![Vec2::ZERO; size];
+
+        for obstacle in obstacles {
+            let obstacle_min = Self::cell_of(obstacle.min);
+            let obstacle_max = Self::cell_of(obstacle.max);
+
+            for x in obstacle_min.x..=obstacle_max.x {
+                for y in obstacle_min.y..=obstacle_max.y {
+                    if let Some(index) = self.index(IVec2::new(x, y)) {
+                        self.blocked[index] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // Breadth first integration out from the target within the window around it,
+    // then point each cell at its cheapest neighbour
+    pub fn build(&mut self, target: Vec2) {
+        let target_cell = Self::cell_of(target);
+        self.target = Some(target_cell);
+
+        // Only the previous window can hold stale values
+        if let Some(window) = self.window.take() {
+            for y in window.min.y..=window.max.y {
+                for x in window.min.x..=window.max.x {
+                    let index = self.index(IVec2::new(x, y)).unwrap();
+                    self.costs[index] = UNREACHABLE;
+                    self.directions[index] = Vec2::ZERO;
+                }
+            }
+        }
+
+        let Some(target_index) = self.index(target_cell) else {
+            return;
+        };
+
+        let window = IRect::from_corners(target_cell - Self::WINDOW, target_cell + Self::WINDOW)
+            .intersect(IRect::from_corners(self.origin, self.origin + IVec2::new(self.width - 1, self.height - 1)));
+        self.window = Some(window);
+
+        let mut queue = VecDeque::new();
+        self.costs[target_index] = 0;
+        queue.push_back(target_cell);
+
+        while let Some(cell) = queue.pop_front() {
+            let cost = self.costs[self.index(cell).unwrap()] + 1;
+
+            for offset in &NEIGHBOURS[..4] {
+                let next = cell + *offset;
+                if !window.contains(next) {
+                    continue;
+                }
+
+                if let Some(index) = self.index(next) {
+                    if !self.blocked[index] && self.costs[index] == UNREACHABLE {
+                        self.costs[index] = cost;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        for y in window.min.y..=window.max.y {
+            for x in window.min.x..=window.max.x {
+                let cell = IVec2::new(x, y);
+                let index = self.index(cell).unwrap();
+
+                if self.costs[index] == UNREACHABLE || cell == target_cell {
+                    continue;
+                }
+
+                let mut best = self.costs[index];
+                for offset in NEIGHBOURS {
+                    // Diagonals only when both sides are open, so enemies don't clip corners
+                    if offset.x != 0 && offset.y != 0
+                        && (!self.is_open(cell + IVec2::new(offset.x, 0)) || !self.is_open(cell + IVec2::new(0, offset.y))) {
+                        continue;
+                    }
+
+                    if let Some(next) = self.index(cell + offset) {
+                        if self.costs[next] < best {
+                            best = self.costs[next];
+                            self.directions[index] = offset.as_vec2().normalize();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // None outside the field, in the target cell, or where the target can't be reached
+    pub fn sample(&self, position: Vec2) -> Option<Vec2> {
+        self.index(Self::cell_of(position))
+            .map(|index| self.directions[index])
+            .filter(|direction| *direction != Vec2::ZERO)
+    }
+}
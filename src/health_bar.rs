@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use crate::components::{Health, HealthBar, MaxHealth, RunScope, RunState, WorldHealthBar};
+use crate::events::DamageApplied;
+
+const BAR_HEIGHT: f32 = 5.;
+
+// Every bar sits on the same z so the sprites sort into one run of backgrounds and one run
+// of fills, and since they all use the default white image each run is a single batch
+const BAR_Z: f32 = 50.;
+const FILL_Z: f32 = 0.1;
+
+// Fully visible for a while after the last hit, then fades out and is despawned
+const VISIBLE_TIME: f32 = 2.5;
+const FADE_TIME: f32 = 0.5;
+
+const BACKGROUND_COLOR: Color = Color::srgba(0., 0., 0., 0.6);
+const FILL_COLOR: Color = Color::srgb(0.85, 0.15, 0.15);
+
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                show_damaged_bars,
+                follow_targets,
+                fade_bars,
+        ).chain().run_if(in_state(RunState::Playing)));
+    }
+}
+
+impl HealthBar {
+    pub fn new(width: f32, offset: f32) -> Self {
+        HealthBar {
+            width,
+            offset,
+            bar: None,
+        }
+    }
+}
+
+// Only entities that took damage get a bar, so untouched crowds cost nothing
+fn show_damaged_bars(
+    mut commands: Commands,
+    mut applied_events: EventReader<DamageApplied>,
+    mut target_query: Query<(&Health, &MaxHealth, &mut HealthBar, &Transform)>,
+    mut bar_query: Query<&mut WorldHealthBar>,
+    mut fill_query: Query<&mut Transform, Without<HealthBar>>,
+) {
+    for event in applied_events.read() {
+        let Ok((health, max_health, mut health_bar, transform)) = target_query.get_mut(event.target) else {
+            continue;
+        };
+        // Killing blows don't need a bar
+        if health.hp <= 0 {
+            continue;
+        }
+        let fraction = (health.hp as f32 / max_health.0 as f32).clamp(0., 1.);
+
+        if let Some(mut bar) = health_bar.bar.and_then(|entity| bar_query.get_mut(entity).ok()) {
+            bar.fade.reset();
+            if let Ok(mut fill_transform) = fill_query.get_mut(bar.fill) {
+                fill_transform.scale.x = fraction;
+            }
+            continue;
+        }
+
+        let width = health_bar.width;
+        let fill = commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: FILL_COLOR,
+                custom_size: Some(Vec2::new(width, BAR_HEIGHT)),
+                anchor: Anchor::CenterLeft,
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: Vec3::new(-width / 2., 0., FILL_Z),
+                scale: Vec3::new(fraction, 1., 1.),
+                ..Default::default()
+            },
+            ..Default::default()
+        }).id();
+
+        let translation = transform.translation.truncate() + Vec2::Y * health_bar.offset;
+        let bar = commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: BACKGROUND_COLOR,
+                        custom_size: Some(Vec2::new(width, BAR_HEIGHT)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(translation.extend(BAR_Z)),
+                    ..Default::default()
+                },
+                WorldHealthBar {
+                    target: event.target,
+                    fill,
+                    fade: Timer::from_seconds(VISIBLE_TIME + FADE_TIME, TimerMode::Once),
+                },
+                RunScope,
+        ))
+            .add_child(fill)
+            .id();
+
+        health_bar.bar = Some(bar);
+    }
+}
+
+// Bars aren't parented to their target so they ignore its scale and flip
+fn follow_targets(
+    mut commands: Commands,
+    target_query: Query<(&Transform, &HealthBar), Without<WorldHealthBar>>,
+    mut bar_query: Query<(Entity, &WorldHealthBar, &mut Transform)>,
+) {
+    for (entity, bar, mut transform) in bar_query.iter_mut() {
+        let Ok((target_transform, health_bar)) = target_query.get(bar.target) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let translation = target_transform.translation.truncate() + Vec2::Y * health_bar.offset;
+        transform.translation = translation.extend(BAR_Z);
+    }
+}
+
+fn fade_bars(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bar_query: Query<(Entity, &mut WorldHealthBar, &mut Sprite)>,
+    mut fill_query: Query<&mut Sprite, Without<WorldHealthBar>>,
+    mut target_query: Query<&mut HealthBar>,
+) {
+    for (entity, mut bar, mut sprite) in bar_query.iter_mut() {
+        if bar.fade.tick(time.delta()).finished() {
+            if let Ok(mut health_bar) = target_query.get_mut(bar.target) {
+                health_bar.bar = None;
+            }
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = 1. - ((bar.fade.elapsed_secs() - VISIBLE_TIME) / FADE_TIME).clamp(0., 1.);
+        sprite.color = BACKGROUND_COLOR.with_alpha(BACKGROUND_COLOR.alpha() * alpha);
+        if let Ok(mut fill_sprite) = fill_query.get_mut(bar.fill) {
+            fill_sprite.color = FILL_COLOR.with_alpha(alpha);
+        }
+    }
+}
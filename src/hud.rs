@@ -0,0 +1,408 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::components::{Ability, AbilitySlot, AppState, Bigfoot, BossBar, BossBarFill, ChargesText, Cooldowns, CooldownRadial, CooldownText, GameTimer, GameTimerText, GameUI, Health, HealthBarFill, HealthText, LifePip, Lives, MaxHealth, Player, RadialAtlas, RunState, Score, ScoreText, UiTheme};
+use crate::PLAYER_LIVES;
+
+// The radial overlay is a strip of pie masks, frame n covers n / (FRAMES - 1) of the circle
+const RADIAL_FRAMES: u32 = 32;
+const RADIAL_PIXELS: u32 = 64;
+
+const SLOT_SIZE: f32 = 64.;
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(300., 24.);
+const BOSS_BAR_SIZE: Vec2 = Vec2::new(500., 18.);
+const PIP_SIZE: f32 = 16.;
+
+const HEALTH_COLOR: Color = Color::srgb(0.8, 0.15, 0.15);
+const BOSS_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+const PIP_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const BAR_BACKGROUND: Color = Color::srgba(0., 0., 0., 0.6);
+
+// The cooldown countdown, kept apart from the charges text on the same slot
+type CooldownTextFilter = (With<CooldownText>, Without<ChargesText>);
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_radial_atlas)
+            .add_systems(OnEnter(AppState::InRun), spawn_hud)
+            .add_systems(Update, (
+                    update_health_bar,
+                    update_life_pips,
+                    update_ability_slots,
+                    update_boss_bar,
+                    update_hud_text,
+            ).run_if(in_state(RunState::Playing)));
+    }
+}
+
+fn ability_label(ability: Ability) -> &'static str {
+    match ability {
+        Ability::Attack => "Attack",
+        Ability::Ranged => "Ranged",
+        Ability::Dash => "Dash",
+        Ability::Aoe => "Bladestorm",
+    }
+}
+
+// Dark translucent pie masks, swept clockwise from twelve o'clock
+fn create_radial_atlas(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: RADIAL_PIXELS * RADIAL_FRAMES,
+            height: RADIAL_PIXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+
+    let half = RADIAL_PIXELS as f32 / 2.;
+    let row_width = RADIAL_PIXELS * RADIAL_FRAMES;
+    for frame in 0..RADIAL_FRAMES {
+        let covered = frame as f32 / (RADIAL_FRAMES - 1) as f32;
+
+        for y in 0..RADIAL_PIXELS {
+            for x in 0..RADIAL_PIXELS {
+                let offset = Vec2::new(x as f32 + 0.5 - half, half - y as f32 - 0.5);
+                let angle = offset.x.atan2(offset.y).rem_euclid(TAU) / TAU;
+
+                if angle < covered {
+                    let index = ((y * row_width + frame * RADIAL_PIXELS + x) * 4) as usize;
+                    image.data[index..index + 4].copy_from_slice(&[0, 0, 0, 170]);
+                }
+            }
+        }
+    }
+
+    let layout = TextureAtlasLayout::from_grid(UVec2::splat(RADIAL_PIXELS), RADIAL_FRAMES, 1, None, None);
+
+    commands.insert_resource(RadialAtlas {
+        image: images.add(image),
+        layout: layouts.add(layout),
+    });
+}
+
+fn spawn_bar(parent: &mut ChildBuilder, size: Vec2, color: Color, fill: impl Bundle) {
+    parent.spawn(NodeBundle {
+        style: Style {
+            width: Val::Px(size.x),
+            height: Val::Px(size.y),
+            ..Default::default()
+        },
+        background_color: BAR_BACKGROUND.into(),
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..Default::default()
+                    },
+                    background_color: color.into(),
+                    ..Default::default()
+                },
+                fill,
+        ));
+    });
+}
+
+fn spawn_hud(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    radial: Res<RadialAtlas>,
+) {
+    commands.spawn((
+            TextBundle::from_section(
+                "WASD to Move around, Q to Melee, E for Ranged, R to swap Beam/Projectile, T for AoE, F to Dash",
+                theme.text_style(24.0),
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.),
+                left: Val::Px(12.),
+                ..Default::default()
+            }),
+            GameUI,
+            StateScoped(AppState::InRun),
+    ));
+
+    // Health, lives, time and score down the left
+    commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.),
+                    left: Val::Px(10.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            GameUI,
+            StateScoped(AppState::InRun),
+    ))
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                spawn_bar(parent, HEALTH_BAR_SIZE, HEALTH_COLOR, HealthBarFill);
+
+                // Drawn over the bar rather than next to it
+                parent.spawn((
+                        TextBundle::from_section("", theme.text_style(20.)).with_style(Style {
+                            position_type: PositionType::Absolute,
+                            ..Default::default()
+                        }),
+                        HealthText,
+                ));
+            });
+
+            // One pip per extra life, spent ones dim out
+            parent.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(6.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                for index in 0..PLAYER_LIVES {
+                    parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(PIP_SIZE),
+                                    height: Val::Px(PIP_SIZE),
+                                    ..Default::default()
+                                },
+                                background_color: PIP_COLOR.into(),
+                                ..Default::default()
+                            },
+                            LifePip(index),
+                    ));
+                }
+            });
+
+            parent.spawn((TextBundle::from_section("Time: 0.0", theme.text_style(40.)), GameTimerText));
+            parent.spawn((TextBundle::from_section("Score: 0", theme.text_style(40.)), ScoreText));
+        });
+
+    // Ability slots along the bottom, each piece knows its ability so query order doesn't matter
+    commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(10.),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(24.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            GameUI,
+            StateScoped(AppState::InRun),
+    ))
+        .with_children(|parent| {
+            for ability in Ability::ALL {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(SLOT_SIZE),
+                            height: Val::Px(SLOT_SIZE),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: Color::srgba(0.9, 0.9, 0.9, 0.5).into(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                                ImageBundle {
+                                    image: UiImage::new(radial.image.clone()),
+                                    style: Style {
+                                        position_type: PositionType::Absolute,
+                                        width: Val::Percent(100.),
+                                        height: Val::Percent(100.),
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                },
+                                TextureAtlas {
+                                    layout: radial.layout.clone(),
+                                    index: 0,
+                                },
+                                CooldownRadial,
+                                AbilitySlot(ability),
+                        ));
+                        parent.spawn((
+                                TextBundle::from_section("", theme.text_style(26.)),
+                                CooldownText,
+                                AbilitySlot(ability),
+                        ));
+                        // Charges left, empty for abilities without any
+                        parent.spawn((
+                                TextBundle::from_section("", theme.text_style(20.)).with_style(Style {
+                                    position_type: PositionType::Absolute,
+                                    right: Val::Px(4.),
+                                    bottom: Val::Px(2.),
+                                    ..Default::default()
+                                }),
+                                ChargesText,
+                                AbilitySlot(ability),
+                        ));
+                    });
+
+                    parent.spawn(TextBundle::from_section(ability_label(ability), theme.text_style(22.)));
+                });
+            }
+        });
+
+    // Boss bar, only shown while the Gashadokuro is around
+    commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(12.),
+                    width: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            BossBar,
+            GameUI,
+            StateScoped(AppState::InRun),
+    ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Gashadokuro", theme.text_style(28.)));
+            spawn_bar(parent, BOSS_BAR_SIZE, BOSS_COLOR, BossBarFill);
+        });
+}
+
+fn update_health_bar(
+    player_query: Query<(&Health, &MaxHealth), With<Player>>,
+    mut fill_query: Query<&mut Style, With<HealthBarFill>>,
+    mut text_query: Query<&mut Text, With<HealthText>>,
+) {
+    let Ok((health, max_health)) = player_query.get_single() else {
+        return;
+    };
+    let fraction = (health.hp as f32 / max_health.0 as f32).clamp(0., 1.);
+
+    for mut style in fill_query.iter_mut() {
+        style.width = Val::Percent(100. * fraction);
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("{} / {}", health.hp.max(0), max_health.0);
+    }
+}
+
+fn update_life_pips(
+    player_query: Query<&Lives, (With<Player>, Changed<Lives>)>,
+    mut pip_query: Query<(&LifePip, &mut BackgroundColor)>,
+) {
+    let Ok(lives) = player_query.get_single() else {
+        return;
+    };
+
+    for (pip, mut background) in pip_query.iter_mut() {
+        background.0 = if pip.0 < lives.0 { PIP_COLOR } else { BAR_BACKGROUND };
+    }
+}
+
+fn update_ability_slots(
+    cooldowns_query: Query<&Cooldowns, With<Player>>,
+    mut radial_query: Query<(&AbilitySlot, &mut TextureAtlas), With<CooldownRadial>>,
+    mut text_query: Query<(&AbilitySlot, &mut Text), CooldownTextFilter>,
+    mut charges_query: Query<(&AbilitySlot, &mut Text), With<ChargesText>>,
+) {
+    let Ok(cooldowns) = cooldowns_query.get_single() else {
+        return;
+    };
+
+    // An ability with a charge left is usable even while the next one recharges
+    for (slot, mut atlas) in radial_query.iter_mut() {
+        let remaining = if cooldowns.is_ready(slot.0) { 0. } else { cooldowns.remaining_fraction(slot.0) };
+        let index = (remaining * (RADIAL_FRAMES - 1) as f32).ceil() as usize;
+        if atlas.index != index {
+            atlas.index = index;
+        }
+    }
+
+    for (slot, mut text) in text_query.iter_mut() {
+        let remaining = if cooldowns.is_ready(slot.0) { 0. } else { cooldowns.get_cooldown(slot.0).unwrap_or(0.) };
+        text.sections[0].value = if remaining > 0. { format!("{:.1}", remaining) } else { String::new() };
+    }
+
+    for (slot, mut text) in charges_query.iter_mut() {
+        text.sections[0].value = cooldowns.charges(slot.0).map_or(String::new(), |charges| charges.current.to_string());
+    }
+}
+
+fn update_boss_bar(
+    bigfoot_query: Query<(&Health, &MaxHealth), With<Bigfoot>>,
+    mut root_query: Query<&mut Visibility, With<BossBar>>,
+    mut fill_query: Query<&mut Style, With<BossBarFill>>,
+) {
+    let boss = bigfoot_query.get_single().ok();
+
+    for mut visibility in root_query.iter_mut() {
+        visibility.set_if_neq(if boss.is_some() { Visibility::Inherited } else { Visibility::Hidden });
+    }
+
+    let Some((health, max_health)) = boss else {
+        return;
+    };
+    let fraction = (health.hp as f32 / max_health.0 as f32).clamp(0., 1.);
+
+    for mut style in fill_query.iter_mut() {
+        style.width = Val::Percent(100. * fraction);
+    }
+}
+
+fn update_hud_text(
+    score: Res<Score>,
+    timer: Res<GameTimer>,
+    mut score_query: Query<&mut Text, (With<ScoreText>, Without<GameTimerText>)>,
+    mut timer_query: Query<&mut Text, With<GameTimerText>>,
+) {
+    for mut text in score_query.iter_mut() {
+        text.sections[0].value = format!("Score: {}  Kills: {}  XP: {}", score.points, score.get_enemies_killed(), score.xp);
+    }
+
+    for mut text in timer_query.iter_mut() {
+        text.sections[0].value = format!("Time: {}", f32::trunc(timer.0 * 100.0) / 100.);
+    }
+}
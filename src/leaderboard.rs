@@ -0,0 +1,310 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::components::{AppState, GameTimer, Leaderboard, LeaderboardEntry, MapSeed, MenuUI, Score, UiTheme};
+use crate::settings::data_dir;
+use crate::widgets::label;
+use crate::{setup_game_over_screen, won_game};
+
+const LEADERBOARD_FILE: &str = "leaderboard.txt";
+
+// Bump when the line layout or the meaning of a field changes and teach `parse` the old one
+// 2: score is combo points, in 1 it was the kill count
+const LEADERBOARD_VERSION: u32 = 2;
+const LEADERBOARD_SIZE: usize = 10;
+
+const ROW_FONT_SIZE: f32 = 24.0;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboard::load())
+            .add_systems(OnEnter(AppState::Menu), spawn_leaderboard)
+            .add_systems(OnEnter(AppState::GameOver), record_run.before(setup_game_over_screen))
+            .add_systems(OnEnter(AppState::Won), record_run.before(won_game));
+    }
+}
+
+impl Leaderboard {
+    pub fn load() -> Self {
+        let Some(path) = leaderboard_path() else {
+            return Leaderboard::default();
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                if error.kind() != ErrorKind::NotFound {
+                    warn!("Could not read the leaderboard at {}: {}", path.display(), error);
+                }
+                return Leaderboard::default();
+            }
+        };
+
+        match parse(&contents) {
+            Some(entries) => Leaderboard {
+                entries,
+                last_rank: None,
+            },
+            None => {
+                // Keep the unreadable or outdated file around instead of writing over it
+                let backup = path.with_extension("txt.bak");
+                warn!("Unrecognised or outdated leaderboard file, moving it to {}", backup.display());
+                let _ = fs::rename(&path, backup);
+                Leaderboard::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = leaderboard_path() else {
+            return;
+        };
+
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(error) = fs::write(&path, self.to_file()) {
+            warn!("Could not save the leaderboard to {}: {}", path.display(), error);
+        }
+    }
+
+    // Header line, then one tab separated line per entry
+    fn to_file(&self) -> String {
+        let mut contents = format!("version {}\n", LEADERBOARD_VERSION);
+        for entry in self.entries.iter() {
+            contents.push_str(&format!(
+                "{}\t{:.2}\t{}\t{}\t{}\t{}\n",
+                entry.score,
+                entry.time_survived,
+                entry.won,
+                entry.seed,
+                entry.date,
+                entry.version,
+            ));
+        }
+        contents
+    }
+
+    // Slots the run in by score, then by time survived, and drops whatever falls off the end
+    pub fn record(&mut self, entry: LeaderboardEntry) {
+        let rank = self.entries
+            .iter()
+            .position(|other| (entry.score, entry.time_survived) > (other.score, other.time_survived))
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        self.last_rank = (rank < LEADERBOARD_SIZE).then_some(rank);
+    }
+
+    pub fn is_personal_best(&self) -> bool {
+        self.last_rank == Some(0)
+    }
+}
+
+fn leaderboard_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(LEADERBOARD_FILE))
+}
+
+// None when the file isn't a leaderboard this build can rank against, single bad lines are skipped
+fn parse(contents: &str) -> Option<Vec<LeaderboardEntry>> {
+    let mut lines = contents.lines();
+    let version: u32 = lines.next()?.strip_prefix("version ")?.trim().parse().ok()?;
+
+    let parse_line = match version {
+        // Kill counts can't be ranked against points, so v1 files are set aside
+        2 => parse_v2,
+        _ => return None,
+    };
+
+    let mut entries: Vec<LeaderboardEntry> = lines
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_line)
+        .collect();
+    entries.truncate(LEADERBOARD_SIZE);
+    Some(entries)
+}
+
+// score, time survived, won, seed, date, build version, tab separated
+fn parse_v2(line: &str) -> Option<LeaderboardEntry> {
+    let mut fields = line.split('\t');
+
+    Some(LeaderboardEntry {
+        score: fields.next()?.parse().ok()?,
+        time_survived: fields.next()?.parse().ok()?,
+        won: fields.next()?.parse().ok()?,
+        seed: fields.next()?.parse().ok()?,
+        date: fields.next()?.parse().ok()?,
+        version: fields.next()?.to_string(),
+    })
+}
+
+// Unix seconds to a yyyy-mm-dd date, from Howard Hinnant's civil_from_days
+fn format_date(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn record_run(
+    state: Res<State<AppState>>,
+    score: Res<Score>,
+    game_timer: Res<GameTimer>,
+    map_seed: Res<MapSeed>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    let date = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+
+    leaderboard.record(LeaderboardEntry {
+        score: score.points,
+        time_survived: game_timer.0,
+        won: *state.get() == AppState::Won,
+        seed: map_seed.0,
+        date,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    });
+    leaderboard.save();
+}
+
+fn spawn_leaderboard(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    leaderboard: Res<Leaderboard>,
+) {
+    commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(30.0),
+                    bottom: Val::Px(30.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.0)),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+                ..Default::default()
+            },
+            MenuUI,
+            StateScoped(AppState::Menu),
+    ))
+        .with_children(|parent| {
+            label(parent, &theme, "Best Runs", 36.0);
+
+            if leaderboard.entries.is_empty() {
+                label(parent, &theme, "No runs yet", ROW_FONT_SIZE);
+            }
+
+            for (rank, entry) in leaderboard.entries.iter().enumerate() {
+                let row = format!(
+                    "#{:<2} {:>6} pts  {:>6.1}s  {}  {}",
+                    rank + 1,
+                    entry.score,
+                    entry.time_survived,
+                    if entry.won { "Won " } else { "Lost" },
+                    format_date(entry.date),
+                );
+                parent.spawn(TextBundle::from_section(row, theme.text_style(ROW_FONT_SIZE)));
+            }
+        });
+}
+
+// Called out on the end of run screens when the run made the table
+pub fn spawn_rank_banner(parent: &mut ChildBuilder, theme: &UiTheme, leaderboard: &Leaderboard) {
+    let Some(rank) = leaderboard.last_rank else {
+        return;
+    };
+
+    if leaderboard.is_personal_best() {
+        parent.spawn(TextBundle::from_section(
+            "New Personal Best!",
+            TextStyle {
+                color: Color::srgb(1.0, 0.85, 0.1),
+                ..theme.text_style(50.0)
+            },
+        ));
+    } else {
+        label(parent, theme, format!("Rank #{} on the leaderboard", rank + 1), 36.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            score,
+            time_survived: 92.5,
+            won: score > 100,
+            seed: 1234567890123,
+            date: 1_700_000_000,
+            version: "0.1.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn saved_file_parses_back_to_the_same_entries() {
+        let leaderboard = Leaderboard {
+            entries: vec![entry(400), entry(250), entry(15)],
+            last_rank: None,
+        };
+
+        assert_eq!(parse(&leaderboard.to_file()), Some(leaderboard.entries));
+    }
+
+    #[test]
+    fn unknown_and_outdated_versions_are_rejected() {
+        assert_eq!(parse("version 99\n400\t92.50\ttrue\t1\t0\t0.1.0\n"), None);
+        // v1 scores were kill counts
+        assert_eq!(parse("version 1\n40\t92.50\ttrue\t1\t0\t0.1.0\n"), None);
+        assert_eq!(parse("400\t92.50\ttrue\t1\t0\t0.1.0\n"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn bad_lines_are_skipped() {
+        let contents = format!(
+            "version {}\n400\t92.50\ttrue\t1\t0\t0.1.0\nnot a run\n250\tslow\tfalse\t1\t0\t0.1.0\n\n15\t3.00\tfalse\t2\t0\t0.1.0\n",
+            LEADERBOARD_VERSION,
+        );
+        let scores: Vec<u32> = parse(&contents).unwrap().iter().map(|entry| entry.score).collect();
+
+        assert_eq!(scores, vec![400, 15]);
+    }
+
+    #[test]
+    fn record_keeps_the_best_runs_in_order() {
+        let mut leaderboard = Leaderboard::default();
+        for score in 0..LEADERBOARD_SIZE as u32 {
+            leaderboard.record(entry(score * 10));
+        }
+
+        leaderboard.record(entry(5));
+        assert_eq!(leaderboard.last_rank, Some(LEADERBOARD_SIZE - 1));
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+
+        leaderboard.record(entry(1000));
+        assert!(leaderboard.is_personal_best());
+    }
+
+    #[test]
+    fn dates_format_as_year_month_day() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+    }
+}
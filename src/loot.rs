@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::{Collider, Cooldowns, DropTable, Health, MapGrid, MaxHealth, Pickup, PickupKind, PickupRadius, Player, RunScope, RunState, Score};
+use crate::events::{ChunkUnloaded, CollisionEvent, EntityDied};
+use crate::clean_dead;
+
+const PICKUP_SIZE: f32 = 16.;
+const PICKUP_SPEED: f32 = 600.;
+
+// Where the player is and how far away pickups start homing in
+type PickupReach = (&'static Transform, &'static PickupRadius);
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (
+                roll_drops.before(clean_dead),
+                attract_pickups,
+                collect_pickups,
+                despawn_unloaded_pickups,
+            ).run_if(in_state(RunState::Playing)));
+    }
+}
+
+fn roll_drops(
+    mut commands: Commands,
+    mut died_events: EventReader<EntityDied>,
+    query: Query<(&Transform, &DropTable)>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for event in died_events.read() {
+        if let Ok((transform, drop_table)) = query.get(event.entity) {
+            for (kind, chance) in drop_table.entries.iter() {
+                if rng.gen::<f32>() < *chance {
+                    // Scatter the drops a little so they don't stack on one spot
+                    let offset = Vec2::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+                    spawn_pickup(&mut commands, *kind, transform.translation.truncate() + offset);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_pickup(
+    commands: &mut Commands,
+    kind: PickupKind,
+    position: Vec2,
+) {
+    let color = match kind {
+        PickupKind::Xp(_) => Color::srgb(0.2, 0.9, 1.0),
+        PickupKind::Health(_) => Color::srgb(0.2, 1.0, 0.3),
+        PickupKind::CooldownRefresh => Color::srgb(0.3, 0.3, 1.0),
+        PickupKind::Magnet => Color::srgb(0.8, 0.2, 0.9),
+    };
+
+    commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(PICKUP_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(position.x, position.y, 5.),
+                    rotation: Quat::from_rotation_z(std::f32::consts::PI / 4.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Collider::new(Vec2::splat(PICKUP_SIZE)),
+            Pickup {
+                kind,
+                attracted: false,
+            },
+            RunScope,
+    ));
+}
+
+fn attract_pickups(
+    time: Res<Time>,
+    player_query: Query<PickupReach, (With<Player>, Without<Pickup>)>,
+    mut pickup_query: Query<(&mut Transform, &mut Pickup)>,
+) {
+    if let Ok((player_transform, pickup_radius)) = player_query.get_single() {
+        let player_position = player_transform.translation.truncate();
+
+        for (mut transform, mut pickup) in pickup_query.iter_mut() {
+            let offset = player_position - transform.translation.truncate();
+
+            // Once a pickup starts flying it keeps homing even if the player outruns the radius
+            if offset.length() < pickup_radius.0 {
+                pickup.attracted = true;
+            }
+
+            if pickup.attracted {
+                let step = (PICKUP_SPEED * time.delta_seconds()).min(offset.length());
+                let movement = offset.normalize_or_zero() * step;
+                transform.translation.x += movement.x;
+                transform.translation.y += movement.y;
+            }
+        }
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut collision_reader: EventReader<CollisionEvent>,
+    mut pickup_query: Query<&mut Pickup>,
+    mut player_query: Query<(&mut Health, &MaxHealth, &mut Cooldowns), With<Player>>,
+    mut score: ResMut<Score>,
+) {
+    let mut collected = vec![];
+
+    for event in collision_reader.read() {
+        let CollisionEvent::Pickup(entity) = event else {
+            continue;
+        };
+
+        // The same pickup can be reported again before its despawn is applied
+        if collected.contains(entity) {
+            continue;
+        }
+
+        let Ok(kind) = pickup_query.get(*entity).map(|pickup| pickup.kind) else {
+            continue;
+        };
+
+        if let Ok((mut health, max_health, mut cooldowns)) = player_query.get_single_mut() {
+            // Nothing is picked up while the death animation plays
+            if health.hp <= 0 {
+                continue;
+            }
+
+            match kind {
+                PickupKind::Xp(amount) => score.add_xp(amount),
+                PickupKind::Health(amount) => health.hp = (health.hp + amount).min(max_health.0),
+                PickupKind::CooldownRefresh => cooldowns.refresh_all(),
+                PickupKind::Magnet => {
+                    for mut other in pickup_query.iter_mut() {
+                        other.attracted = true;
+                    }
+                }
+            }
+
+            collected.push(*entity);
+            commands.entity(*entity).despawn();
+        }
+    }
+}
+
+// Pickups left behind in a chunk go away with it
+fn despawn_unloaded_pickups(
+    mut commands: Commands,
+    mut unloaded_events: EventReader<ChunkUnloaded>,
+    query: Query<(Entity, &Transform), With<Pickup>>,
+) {
+    for event in unloaded_events.read() {
+        for (entity, transform) in query.iter() {
+            if MapGrid::cell_of(transform.translation.truncate()) == event.cell {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
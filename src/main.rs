@@ -1,16 +1,51 @@
+mod camera;
 mod components;
 mod collision;
+mod combo;
+mod damage;
 mod enemy;
 mod player;
+mod run;
+mod settings;
 mod systems;
+mod threat;
 mod events;
+mod feedback;
+mod flow_field;
+mod health_bar;
+mod hud;
+mod leaderboard;
+mod loot;
+mod map;
+mod minimap;
+mod states;
+mod stats;
+mod status;
+mod widgets;
 
 use bevy::prelude::*;
+use camera::CameraPlugin;
 use collision::CollisionPlugin;
+use combo::ComboPlugin;
+use damage::DamagePlugin;
 use enemy::EnemyPlugin;
+use feedback::FeedbackPlugin;
+use health_bar::HealthBarPlugin;
+use hud::HudPlugin;
+use leaderboard::LeaderboardPlugin;
+use loot::LootPlugin;
+use map::MapPlugin;
+use minimap::MinimapPlugin;
+use threat::ThreatPlugin;
 use player::PlayerPlugin;
+use run::{RunPlugin, RunScopeAppExt};
+use settings::SettingsPlugin;
+use states::StatePlugin;
+use stats::StatsPlugin;
+use status::StatusPlugin;
+use widgets::WidgetPlugin;
 use events::*;
-use components::{CurrentGameState, GameState, GameTimer, MapGrid, MousePosition, Points, Score};
+use components::{GameTimer, MousePosition, Points, RunState, RunSummary, Score};
 use systems::*;
 
 //Assets constants
@@ -23,10 +58,18 @@ const SPRITE_SCALE: f32 = 0.5;
 
 // Game Cosntants
 const BASE_SPEED: f32 = 250.;
+const PLAYER_HEALTH: i32 = 500;
+const PLAYER_LIVES: u32 = 1;
 const PLAYER_RADIUS: f32 = 500.;
+const DASH_DISTANCE: f32 = 400.;
+const DASH_DURATION: f32 = 0.2;
 
 // Enemy Constants
 const ENEMY_SPEED: f32 = 150.;
+const ONI_HEALTH: i32 = 1;
+const ONI_POINTS: u32 = 10;
+const BIGFOOT_HEALTH: i32 = 20;
+const BIGFOOT_HIT_INVULNERABILITY: f32 = 0.25;
 
 // Resources
 #[derive(Resource)]
@@ -47,51 +90,62 @@ pub struct MouseCoords {
 #[derive(Resource)]
 struct EnemySpawnRate(f32);
 
+impl Default for EnemySpawnRate {
+    fn default() -> Self {
+        Self(2.0)
+    }
+}
+
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
+        .add_plugins(StatePlugin)
+        .add_plugins(WidgetPlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(RunPlugin)
         .add_plugins(CollisionPlugin)
+        .add_plugins(DamagePlugin)
         .add_plugins(PlayerPlugin)
         .add_plugins(EnemyPlugin)
-        .insert_resource(Score::new())
+        .add_plugins(StatusPlugin)
+        .add_plugins(FeedbackPlugin)
+        .add_plugins(CameraPlugin)
+        .add_plugins(LootPlugin)
+        .add_plugins(MapPlugin)
+        .add_plugins(MinimapPlugin)
+        .add_plugins(ThreatPlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(ComboPlugin)
+        .add_plugins(HudPlugin)
+        .add_plugins(HealthBarPlugin)
+        .add_plugins(LeaderboardPlugin)
         .insert_resource(MousePosition::default())
-        .insert_resource(Points::default())
-        .insert_resource(CurrentGameState { state: GameState::Menu }) 
-        .insert_resource(MapGrid::default()) 
-        .insert_resource(GameTimer(0.0))
-        .init_state::<GameState>()
-        .add_systems(Startup, (setup, setup_menu))
-        .add_systems(OnExit(GameState::Menu), (kill_wallpaper, despawn_menu, spawn_menu, setup_pause_menu))
-
-        .add_systems(OnEnter(GameState::Menu),(reset_game, kill_game_ui, despawn_menu, setup_menu, reset_game))
-        .add_systems(OnEnter(GameState::Reset),(reset_game))
-        .add_systems(OnExit(GameState::Reset),(kill_wallpaper, kill_game_over_ui, despawn_menu, spawn_menu, setup_pause_menu))
-        .add_systems(OnEnter(GameState::GameOver), setup_game_over_screen)
+        .init_run_resource::<Score>()
+        .init_run_resource::<Points>()
+        .init_run_resource::<GameTimer>()
+        .init_run_resource::<RunSummary>()
+        .init_run_resource::<EnemySpawnRate>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, defeat_bigfoot.run_if(in_state(RunState::Playing)))
         .add_systems(
             FixedUpdate,
             (
                 clean_dead,
-                menu_action_system,
-                quit_action_system,
-                restart_action_system,
-                check_won_game,
-                update_timer.run_if(in_state(GameState::Running)),
-                camera_follow_player.run_if(in_state(GameState::Running)),
-                update_mouse_position.run_if(in_state(GameState::Running)),
-                update_lifetime.run_if(in_state(GameState::Running)),
-                update_cooldowns.run_if(in_state(GameState::Running)),
-                update_cooldowns_ui.run_if(in_state(GameState::Running)),
-                update_ui_text.run_if(in_state(GameState::Running)),
-                manage_invulnerability.run_if(in_state(GameState::Running)),
-                //flicker_system.run_if(in_state(GameState::Running)),
-                check_and_spawn_map.run_if(in_state(GameState::Running)),
-                handle_escape_pressed.run_if(in_state(GameState::Running).or_else(in_state(GameState::Paused))),
-                update_bigfoot.run_if(in_state(GameState::Running)),
-                //update_player_position.run_if(in_state(GameState::Running)),
-                update_bigfoot_position.run_if(in_state(GameState::Running)),
-            ))
+                update_timer,
+                update_mouse_position,
+                update_lifetime,
+                update_cooldowns,
+                manage_invulnerability,
+                //flicker_system,
+                update_bigfoot,
+                //update_player_position,
+                update_bigfoot_position,
+            ).run_if(in_state(RunState::Playing)))
         .add_event::<CollisionEvent>()
-        .add_systems(OnEnter(GameState::Paused), show_pause_menu)
-        .add_systems(OnExit(GameState::Paused), hide_pause_menu)
+        .add_event::<DamageEvent>()
+        .add_event::<DamageApplied>()
+        .add_event::<EntityDied>()
+        .add_event::<PlayerDied>()
+        .add_event::<ScreenShake>()
         .run();
 }
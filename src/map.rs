@@ -0,0 +1,183 @@
+use std::collections::hash_map::Entry;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::components::{Chunk, ChunkTemplates, Map, MapGrid, MapSeed, Player, Prop, RunScope, RunState, StaticCollider};
+use crate::events::{ChunkLoaded, ChunkUnloaded};
+use crate::run::RunScopeAppExt;
+use crate::{GameTextures, MAP_HEIGHT, MAP_WIDTH};
+
+// Keeps props away from chunk edges so they don't overlap the neighbour
+const PROP_MARGIN: f32 = 200.;
+
+const PROP_SEED_SALT: u64 = 0x9A0B_5EED;
+
+// Every cell gets its own rng stream derived from the seed and its coordinates
+pub fn cell_rng(seed: u64, cell: (i32, i32)) -> StdRng {
+    let x = (cell.0 as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    let y = (cell.1 as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+    StdRng::seed_from_u64(seed ^ x ^ y.rotate_left(32))
+}
+
+#[derive(SystemParam)]
+struct ChunkEvents<'w> {
+    loaded: EventWriter<'w, ChunkLoaded>,
+    unloaded: EventWriter<'w, ChunkUnloaded>,
+}
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<MapGrid>()
+            .init_run_resource::<MapSeed>()
+            .insert_resource(ChunkTemplates::default())
+            .add_event::<ChunkLoaded>()
+            .add_event::<ChunkUnloaded>()
+            .add_systems(FixedUpdate, (
+                    update_chunks,
+                    spawn_chunk_props.after(update_chunks),
+            ).run_if(in_state(RunState::Playing)));
+    }
+}
+
+// Keeps the 3x3 block of chunks around the player loaded
+fn update_chunks(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    mut map_grid: ResMut<MapGrid>,
+    game_textures: Res<GameTextures>,
+    chunk_templates: Res<ChunkTemplates>,
+    map_seed: Res<MapSeed>,
+    mut chunk_events: ChunkEvents,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_cell = MapGrid::cell_of(player_transform.translation.truncate());
+
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let cell = (player_cell.0 + dx, player_cell.1 + dy);
+
+            // Looking the cell up mutably would mark the grid changed every tick
+            if let Entry::Vacant(slot) = map_grid.bypass_change_detection().chunks.entry(cell) {
+                let entity = spawn_chunk(&mut commands, &chunk_templates, map_seed.0, game_textures.map.clone(), cell);
+                slot.insert(entity);
+                map_grid.set_changed();
+                chunk_events.loaded.send(ChunkLoaded { cell, entity });
+            }
+        }
+    }
+
+    // Only touch the resource when something actually leaves, so change detection stays meaningful
+    let distant: Vec<(i32, i32)> = map_grid.chunks
+        .keys()
+        .filter(|cell| (cell.0 - player_cell.0).abs() > 1 || (cell.1 - player_cell.1).abs() > 1)
+        .copied()
+        .collect();
+
+    for cell in distant {
+        if let Some(entity) = map_grid.chunks.remove(&cell) {
+            commands.entity(entity).despawn_recursive();
+            chunk_events.unloaded.send(ChunkUnloaded { cell });
+        }
+    }
+}
+
+pub fn spawn_chunk(
+    commands: &mut Commands,
+    templates: &ChunkTemplates,
+    seed: u64,
+    texture: Handle<Image>,
+    cell: (i32, i32),
+) -> Entity {
+    let mut rng = cell_rng(seed, cell);
+
+    let weights = templates.0.iter().map(|template| template.weight);
+    let index = WeightedIndex::new(weights).expect("Chunk templates need a positive weight").sample(&mut rng);
+    let template = &templates.0[index];
+
+    commands.spawn((
+            SpriteBundle {
+                texture,
+                sprite: Sprite {
+                    color: template.tint,
+                    custom_size: Some(Vec2::new(MAP_WIDTH, MAP_HEIGHT)),
+                    flip_x: rng.gen(),
+                    flip_y: rng.gen(),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: MapGrid::cell_center(cell).extend(0.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Map,
+            Chunk {
+                cell,
+                template: index,
+            },
+            RunScope,
+    ))
+    .id()
+}
+
+fn spawn_chunk_props(
+    mut commands: Commands,
+    mut loaded_events: EventReader<ChunkLoaded>,
+    chunk_query: Query<&Chunk>,
+    templates: Res<ChunkTemplates>,
+    map_seed: Res<MapSeed>,
+) {
+    for event in loaded_events.read() {
+        let Ok(chunk) = chunk_query.get(event.entity) else {
+            continue;
+        };
+        let template = &templates.0[chunk.template];
+
+        // Separate stream from the backdrop so either can change without reshuffling the other
+        let mut rng = cell_rng(map_seed.0 ^ PROP_SEED_SALT, chunk.cell);
+
+        let half_width = MAP_WIDTH / 2. - PROP_MARGIN;
+        let half_height = MAP_HEIGHT / 2. - PROP_MARGIN;
+
+        commands.entity(event.entity).with_children(|parent| {
+            for (kind, count) in template.props.iter() {
+                for _ in 0..*count {
+                    let position = Vec2::new(
+                        rng.gen_range(-half_width..half_width),
+                        rng.gen_range(-half_height..half_height),
+                    );
+
+                    let mut prop = parent.spawn((
+                            SpriteBundle {
+                                sprite: Sprite {
+                                    color: kind.color(),
+                                    custom_size: Some(kind.size()),
+                                    ..Default::default()
+                                },
+                                transform: Transform::from_xyz(position.x, position.y, 1.),
+                                ..Default::default()
+                            },
+                            Prop,
+                    ));
+
+                    if kind.is_obstacle() {
+                        // Props are children, so the collider is stored in world space up front
+                        let center = MapGrid::cell_center(chunk.cell) + position;
+                        prop.insert(StaticCollider {
+                            rect: Rect::from_center_size(center, kind.size()),
+                        });
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,243 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::components::{AppState, Bigfoot, Enemy, ExploredChunks, GameUI, MapGrid, Minimap, MinimapUi, Pickup, Player, RunState};
+use crate::events::ChunkLoaded;
+use crate::run::RunScopeAppExt;
+use crate::{MAP_HEIGHT, MAP_WIDTH};
+
+const MINIMAP_KEY: KeyCode = KeyCode::KeyM;
+
+// Size of the minimap texture in pixels and how much of the world it covers
+const MINIMAP_PIXELS: u32 = 160;
+const MINIMAP_SPAN: f32 = 12000.;
+
+// Enemies are counted in square buckets of this many pixels for the heatmap
+const HEAT_BUCKET: u32 = 4;
+const HEAT_MAX: f32 = 6.;
+
+const BACKGROUND: [u8; 4] = [10, 10, 20, 160];
+const EXPLORED: [u8; 4] = [60, 70, 60, 200];
+const LOADED: [u8; 4] = [90, 110, 90, 220];
+const PICKUP: [u8; 4] = [80, 230, 255, 255];
+const PLAYER: [u8; 4] = [255, 255, 255, 255];
+const BOSS: [u8; 4] = [255, 160, 20, 255];
+
+// Everything drawn on top of the chunks
+#[derive(SystemParam)]
+struct MinimapMarkers<'w, 's> {
+    enemies: Query<'w, 's, &'static Transform, With<Enemy>>,
+    pickups: Query<'w, 's, &'static Transform, With<Pickup>>,
+    bosses: Query<'w, 's, &'static Transform, With<Bigfoot>>,
+}
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<ExploredChunks>()
+            .add_systems(Startup, create_minimap_image)
+            .add_systems(OnEnter(AppState::InRun), spawn_minimap)
+            .add_systems(Update, (
+                    record_explored,
+                    toggle_minimap,
+                    draw_minimap.after(record_explored),
+            ).run_if(in_state(RunState::Playing)));
+    }
+}
+
+fn create_minimap_image(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let image = Image::new_fill(
+        Extent3d {
+            width: MINIMAP_PIXELS,
+            height: MINIMAP_PIXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &BACKGROUND,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+
+    commands.insert_resource(Minimap {
+        image: images.add(image),
+        visible: true,
+    });
+}
+
+fn spawn_minimap(
+    mut commands: Commands,
+    minimap: Res<Minimap>,
+) {
+    commands.spawn((
+            ImageBundle {
+                image: UiImage::new(minimap.image.clone()),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(12.),
+                    right: Val::Px(12.),
+                    width: Val::Px(MINIMAP_PIXELS as f32 * 1.5),
+                    height: Val::Px(MINIMAP_PIXELS as f32 * 1.5),
+                    ..Default::default()
+                },
+                visibility: if minimap.visible { Visibility::Visible } else { Visibility::Hidden },
+                ..Default::default()
+            },
+            MinimapUi,
+            GameUI,
+            StateScoped(AppState::InRun),
+    ));
+}
+
+fn record_explored(
+    mut loaded_events: EventReader<ChunkLoaded>,
+    mut explored: ResMut<ExploredChunks>,
+) {
+    for event in loaded_events.read() {
+        explored.0.insert(event.cell);
+    }
+}
+
+fn toggle_minimap(
+    kb: Res<ButtonInput<KeyCode>>,
+    mut minimap: ResMut<Minimap>,
+    mut query: Query<&mut Visibility, With<MinimapUi>>,
+) {
+    if kb.just_pressed(MINIMAP_KEY) {
+        minimap.visible = !minimap.visible;
+
+        for mut visibility in query.iter_mut() {
+            *visibility = if minimap.visible { Visibility::Visible } else { Visibility::Hidden };
+        }
+    }
+}
+
+fn draw_minimap(
+    minimap: Res<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    explored: Res<ExploredChunks>,
+    map_grid: Res<MapGrid>,
+    player_query: Query<&Transform, With<Player>>,
+    markers: MinimapMarkers,
+) {
+    if !minimap.visible {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+
+    let center = player_transform.translation.truncate();
+    let pixels_per_unit = MINIMAP_PIXELS as f32 / MINIMAP_SPAN;
+    let half = MINIMAP_PIXELS as f32 / 2.;
+
+    // World position to pixel coordinates, rows grow downwards
+    let to_pixel = |position: Vec2| {
+        let offset = (position - center) * pixels_per_unit;
+        Vec2::new(half + offset.x, half - offset.y)
+    };
+
+    for pixel in image.data.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&BACKGROUND);
+    }
+
+    for cell in explored.0.iter() {
+        let color = if map_grid.chunks.contains_key(cell) { LOADED } else { EXPLORED };
+        let chunk_center = to_pixel(MapGrid::cell_center(*cell));
+        let chunk_half = Vec2::new(MAP_WIDTH, MAP_HEIGHT) * pixels_per_unit / 2.;
+
+        // Leave a one pixel seam so neighbouring chunks read as separate cells
+        fill_rect(&mut image.data, chunk_center - chunk_half + 1., chunk_center + chunk_half - 1., color);
+    }
+
+    let buckets_per_side = MINIMAP_PIXELS / HEAT_BUCKET;
+    let mut heat = vec![0u32; (buckets_per_side * buckets_per_side) as usize];
+    for transform in markers.enemies.iter() {
+        let pixel = to_pixel(transform.translation.truncate());
+        if inside(pixel) {
+            let bucket = (pixel.y as u32 / HEAT_BUCKET) * buckets_per_side + pixel.x as u32 / HEAT_BUCKET;
+            heat[bucket as usize] += 1;
+        }
+    }
+
+    for (bucket, count) in heat.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+
+        // Blend from a dull red for a stray enemy up to bright red for a dense pack
+        let density = (*count as f32 / HEAT_MAX).min(1.0);
+        let color = [(140. + 115. * density) as u8, 30, 30, (120. + 135. * density) as u8];
+        let min = Vec2::new((bucket as u32 % buckets_per_side) as f32, (bucket as u32 / buckets_per_side) as f32) * HEAT_BUCKET as f32;
+        fill_rect(&mut image.data, min, min + HEAT_BUCKET as f32, color);
+    }
+
+    for transform in markers.pickups.iter() {
+        let pixel = to_pixel(transform.translation.truncate());
+        fill_rect(&mut image.data, pixel - 1., pixel + 1., PICKUP);
+    }
+
+    for transform in markers.bosses.iter() {
+        let pixel = to_pixel(transform.translation.truncate());
+
+        if inside(pixel) {
+            fill_rect(&mut image.data, pixel - 3., pixel + 3., BOSS);
+        } else {
+            // Pin an arrow to the edge of the map pointing the way to the boss
+            let direction = (pixel - half).normalize_or_zero();
+            let edge = Vec2::splat(half) + direction * (half - 1.) / direction.abs().max_element().max(f32::EPSILON);
+            draw_arrow(&mut image.data, edge, direction, BOSS);
+        }
+    }
+
+    fill_rect(&mut image.data, Vec2::splat(half - 2.), Vec2::splat(half + 2.), PLAYER);
+}
+
+fn inside(pixel: Vec2) -> bool {
+    pixel.x >= 0. && pixel.y >= 0. && pixel.x < MINIMAP_PIXELS as f32 && pixel.y < MINIMAP_PIXELS as f32
+}
+
+fn set_pixel(data: &mut [u8], x: i32, y: i32, color: [u8; 4]) {
+    if x < 0 || y < 0 || x >= MINIMAP_PIXELS as i32 || y >= MINIMAP_PIXELS as i32 {
+        return;
+    }
+
+    let index = ((y as u32 * MINIMAP_PIXELS + x as u32) * 4) as usize;
+    data[index..index + 4].copy_from_slice(&color);
+}
+
+fn fill_rect(data: &mut [u8], min: Vec2, max: Vec2, color: [u8; 4]) {
+    let min = min.max(Vec2::ZERO).as_ivec2();
+    let max = max.min(Vec2::splat(MINIMAP_PIXELS as f32)).as_ivec2();
+
+    for y in min.y..max.y {
+        for x in min.x..max.x {
+            set_pixel(data, x, y, color);
+        }
+    }
+}
+
+// Small triangle with its tip at the given pixel, pointing along direction
+fn draw_arrow(data: &mut [u8], tip: Vec2, direction: Vec2, color: [u8; 4]) {
+    let side = direction.perp();
+
+    for y in -8..=8 {
+        for x in -8..=8 {
+            let offset = Vec2::new(x as f32, y as f32);
+            let along = offset.dot(direction);
+            let across = offset.dot(side);
+
+            if (-8.0..=0.0).contains(&along) && across.abs() <= -along / 2. {
+                set_pixel(data, tip.x as i32 + x, tip.y as i32 + y, color);
+            }
+        }
+    }
+}
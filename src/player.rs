@@ -1,18 +1,54 @@
 use std::f32::consts::PI;
-
-use crate::{aoe_sound, dash_sound, play_empty_swing, ranged_sound, spawn_bigfoot, GameTextures, MouseCoords, BASE_SPEED, SPRITE_SCALE, SPRITE_SIZE};
-use crate::components::{Ability, Collider, Cooldowns, GameState, Health, Invulnerability, Lifetime, Line, Player, PointMarker, Points, Velocity}; 
+use std::time::Duration;
+
+use crate::collision::{first_obstacle_hit, segment_intersects_rect};
+use crate::run::{RunScopeAppExt, RunSet};
+use crate::{aoe_sound, boomerang_sound, dash_sound, death_sound, play_empty_swing, ranged_sound, spawn_bigfoot, GameTextures, SoundEffects, MouseCoords, BASE_SPEED, DASH_DISTANCE, DASH_DURATION, PLAYER_HEALTH, PLAYER_LIVES, SPRITE_SCALE, SPRITE_SIZE};
+use crate::components::{Ability, AbilityBuffer, AbilitySource, AppState, Armor, Attackable, Collider, Cooldowns, Crit, DamageKind, Dash, Dying, GameTimer, Health, Invulnerability, Lifetime, Line, Lives, MaxHealth, ObstacleGrid, OnHit, Owner, PickupRadius, Player, PointMarker, Points, Projectile, RangedAttack, RangedKind, RunScope, RunState, RunSummary, Score, StatusEffect, StatusEffects, StatusKind, Velocity};
+use crate::events::{DamageEvent, EntityDied, PlayerDied, ScreenShake}; 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
+// How long a press stays queued while its ability is still on cooldown
+const ABILITY_BUFFER_WINDOW: f32 = 0.15;
+
+const PROJECTILE_SIZE: f32 = 24.;
+const DASH_STUN: f32 = 0.5;
+const DEATH_ANIMATION: f32 = 1.5;
+const REVIVE_INVULNERABILITY: f32 = 2.0;
+
+const ABILITY_KEYS: [(KeyCode, Ability); 4] = [
+    (KeyCode::KeyE, Ability::Ranged),
+    (KeyCode::KeyF, Ability::Dash),
+    (KeyCode::KeyQ, Ability::Attack),
+    (KeyCode::KeyT, Ability::Aoe),
+];
+
+const SWAP_RANGED_KEY: KeyCode = KeyCode::KeyR;
+
+impl FromWorld for AbilityBuffer {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(Duration::from_secs_f32(ABILITY_BUFFER_WINDOW))
+    }
+}
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, (player_spawn_system, spawn_bigfoot.after(player_spawn_system)))
+        app.init_run_resource::<AbilityBuffer>()
+            .add_systems(OnEnter(AppState::InRun), (player_spawn_system, spawn_bigfoot).chain().in_set(RunSet::Build))
+            .add_systems(Update, (buffer_ability_input, swap_ranged_kind).run_if(in_state(RunState::Playing)))
+            // Presses made right before a pause or level up shouldn't fire once play resumes
+            .add_systems(OnExit(RunState::Playing), clear_ability_buffer)
             .add_systems(FixedUpdate, (
                     player_movement_system, 
                     player_keyboard_event_system,
-                    ability_system,).run_if(in_state(GameState::Running)));
+                    ability_system,
+                    dash_movement_system.after(ability_system),
+                    projectile_system.after(ability_system),
+                    check_player_death,
+                    player_dying_system.after(check_player_death),).run_if(in_state(RunState::Playing)));
     }
 }
 
@@ -31,18 +67,103 @@ pub fn player_spawn_system(
                 ..Default::default()
             },
             Health {
-                hp: 500
+                hp: PLAYER_HEALTH
+            },
+            MaxHealth(PLAYER_HEALTH),
+            Lives(PLAYER_LIVES),
+            PickupRadius(150.),
+            Armor(2),
+            Crit {
+                chance: 0.1,
+                multiplier: 2.0,
             },
             Collider::new(Vec2::splat(SPRITE_SIZE.0 * SPRITE_SCALE)),
             Cooldowns::new(),
+            RangedAttack::new(),
+            StatusEffects::default(),
             Player,
             Velocity {
                 x: 0.,
                 y: 0.,
             },
+            RunScope,
     ));
 }
 
+fn check_player_death(
+    mut commands: Commands,
+    mut died_events: EventReader<EntityDied>,
+    query: Query<Entity, (With<Player>, Without<Dying>)>,
+    mut death_events: EventWriter<PlayerDied>,
+    sfx: SoundEffects,
+) {
+    for event in died_events.read() {
+        if !event.is_player {
+            continue;
+        }
+
+        if let Ok(player_entity) = query.get(event.entity) {
+            death_sound(&sfx, &mut commands);
+            death_events.send(PlayerDied);
+
+            commands.entity(player_entity).insert((
+                Dying {
+                    timer: Timer::from_seconds(DEATH_ANIMATION, TimerMode::Once),
+                },
+                Invulnerability {
+                    timer: Timer::from_seconds(DEATH_ANIMATION, TimerMode::Once),
+                },
+            ));
+        }
+    }
+}
+
+fn player_dying_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Dying, &mut Health, &mut Lives, &mut Sprite), With<Player>>,
+    score: Res<Score>,
+    game_timer: Res<GameTimer>,
+    mut run_summary: ResMut<RunSummary>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    if let Ok((player_entity, mut dying, mut health, mut lives, mut sprite)) = query.get_single_mut() {
+        dying.timer.tick(time.delta());
+
+        // Fade the player out over the death animation
+        sprite.color.set_alpha(1.0 - dying.timer.fraction());
+
+        if dying.timer.finished() {
+            if lives.0 > 0 {
+                lives.0 -= 1;
+                revive_player(&mut commands, player_entity, &mut health, &mut sprite);
+                commands.entity(player_entity).insert(Invulnerability {
+                    timer: Timer::from_seconds(REVIVE_INVULNERABILITY, TimerMode::Once),
+                });
+            } else {
+                *run_summary = RunSummary {
+                    enemies_killed: score.get_enemies_killed(),
+                    points: score.points,
+                    time_survived: game_timer.0,
+                    lives_used: PLAYER_LIVES.saturating_sub(lives.0),
+                };
+                state.set(AppState::GameOver);
+            }
+        }
+    }
+}
+
+fn revive_player(
+    commands: &mut Commands,
+    player_entity: Entity,
+    health: &mut Health,
+    sprite: &mut Sprite,
+) {
+    health.hp = PLAYER_HEALTH;
+    sprite.color.set_alpha(1.0);
+    commands.entity(player_entity).remove::<Dying>();
+}
+
 fn player_keyboard_event_system(
     kb: Res<ButtonInput<KeyCode>>,
     mut query: Query<&mut Velocity, With<Player>>
@@ -74,78 +195,126 @@ fn player_keyboard_event_system(
     }
 }
 
+// A dash or the death animation take over from walking
+type Walking = (With<Player>, Without<Dash>, Without<Dying>);
+
 fn player_movement_system(
-    mut query: Query<(&Velocity, &mut Transform), With<Player>>,
+    mut query: Query<(&Velocity, &mut Transform, &StatusEffects), Walking>,
     time: Res<Time>
 ) {
-    for (velocity, mut transform) in query.iter_mut() {
+    for (velocity, mut transform, status_effects) in query.iter_mut() {
         let translation = &mut transform.translation;
+        let speed = BASE_SPEED * status_effects.speed_multiplier();
+
+        translation.x += velocity.x * time.delta_seconds() * speed;
+        translation.y += velocity.y * time.delta_seconds() * speed;
+    }
+}
 
-        translation.x += velocity.x * time.delta_seconds() * BASE_SPEED;
-        translation.y += velocity.y * time.delta_seconds() * BASE_SPEED;
+fn buffer_ability_input(
+    kb: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut buffer: ResMut<AbilityBuffer>,
+) {
+    buffer.tick(time.delta());
+
+    // Keys are checked in a fixed order so simultaneous presses always queue the same way
+    for (key, ability) in ABILITY_KEYS {
+        if kb.just_pressed(key) {
+            buffer.push(ability);
+        }
+    }
+}
+
+fn clear_ability_buffer(mut buffer: ResMut<AbilityBuffer>) {
+    buffer.clear();
+}
+
+// Flips the ranged ability between the instant beam and the projectile
+fn swap_ranged_kind(
+    kb: Res<ButtonInput<KeyCode>>,
+    mut query: Query<&mut RangedAttack, With<Player>>,
+) {
+    if !kb.just_pressed(SWAP_RANGED_KEY) {
+        return;
+    }
+
+    for mut ranged in query.iter_mut() {
+        ranged.kind = match ranged.kind {
+            RangedKind::Beam => RangedKind::Projectile,
+            RangedKind::Projectile => RangedKind::Beam,
+        };
     }
 }
 
 fn ability_system(
     mut commands: Commands,
-    kb: Res<ButtonInput<KeyCode>>,
-    mut cooldown_query: Query<&mut Cooldowns>,
+    mut buffer: ResMut<AbilityBuffer>,
+    mut cooldown_query: Query<&mut Cooldowns, Without<Dying>>,
     mouse_coords: Res<MouseCoords>,
     player_query: Query<(Entity, &mut Transform), With<Player>>,
+    ranged_query: Query<&RangedAttack, With<Player>>,
     game_textures: Res<GameTextures>,
-    points: ResMut<Points>,
-    mut asset_server: Res<AssetServer>,
+    mut points: ResMut<Points>,
+    sfx: SoundEffects,
+    mut shake_events: EventWriter<ScreenShake>,
+    obstacle_grid: Res<ObstacleGrid>,
 ) {
     if let Ok(mut cooldowns) = cooldown_query.get_single_mut() {
-        if kb.just_pressed(KeyCode::KeyE) {
-            if cooldowns.is_ready(Ability::Ranged) {
-                ranged_attack(
-                    &mut commands,
-                    player_query,
-                    mouse_coords,
-                    game_textures);
-                cooldowns.reset(Ability::Ranged);
-                ranged_sound(&mut asset_server, &mut commands);
-            } else {
-                println!("Ranged ability on cooldown!");
-            }
-        } else if kb.just_pressed(KeyCode::KeyF) {
-            if cooldowns.is_ready(Ability::Dash) {
-                dash_attack(
-                    &mut commands,
-                    player_query,
-                    mouse_coords,
-                    game_textures);
-                cooldowns.reset(Ability::Dash);
-                dash_sound(&mut asset_server, &mut commands);
-            } else {
-                println!("Dash is on cooldown!");
-
-            }
-        } else if kb.just_pressed(KeyCode::KeyQ) {
-            if cooldowns.is_ready(Ability::Attack) {
-                melee_attack(
-                    &mut commands,
-                    player_query,
-                    mouse_coords,
-                    game_textures,
-                    points);
-                cooldowns.reset(Ability::Attack);
-                play_empty_swing(asset_server, &mut commands);
-            } else {
-                println!("Arc ability is on cooldown!");
-            }
-        } else if kb.just_pressed(KeyCode::KeyT) {
-            if cooldowns.is_ready(Ability::Aoe) {
-                aoe_attack(
-                    &mut commands, 
-                    player_query, 
-                    game_textures, 
-                    points);
-                cooldowns.reset(Ability::Aoe);
-                aoe_sound(&asset_server, &mut commands)
+        let presses = std::mem::take(&mut buffer.presses);
+
+        for press in presses {
+            if cooldowns.is_ready(press.ability) {
+                match press.ability {
+                    Ability::Ranged => {
+                        if let Ok(ranged) = ranged_query.get_single() {
+                            ranged_attack(
+                                &mut commands,
+                                &player_query,
+                                &mouse_coords,
+                                &game_textures,
+                                &obstacle_grid,
+                                ranged);
+
+                            if ranged.kind == RangedKind::Projectile && ranged.boomerang {
+                                boomerang_sound(&sfx, &mut commands);
+                            } else {
+                                ranged_sound(&sfx, &mut commands);
+                            }
+                        }
+                    }
+                    Ability::Dash => {
+                        dash_attack(
+                            &mut commands,
+                            &player_query,
+                            &mouse_coords);
+                        dash_sound(&sfx, &mut commands);
+                        shake_events.send(ScreenShake { trauma: 0.25 });
+                    }
+                    Ability::Attack => {
+                        melee_attack(
+                            &mut commands,
+                            &player_query,
+                            &mouse_coords,
+                            &game_textures,
+                            &mut points);
+                        play_empty_swing(&sfx, &mut commands);
+                    }
+                    Ability::Aoe => {
+                        aoe_attack(
+                            &mut commands,
+                            &player_query,
+                            &game_textures,
+                            &mut points);
+                        aoe_sound(&sfx, &mut commands);
+                    }
+                }
+                cooldowns.reset(press.ability);
+            } else if press.timer.finished() {
+                debug!("{} is on cooldown, dropping the press", press.ability);
             } else {
-                println!("AOE is on cooldown!");
+                // Still inside the buffer window, try again next tick
+                buffer.presses.push(press);
             }
         }
     }
@@ -153,101 +322,339 @@ fn ability_system(
 
 fn ranged_attack(
     commands: &mut Commands,
-    player_query: Query<(Entity, &mut Transform), With<Player>>,
-    mouse_coords: Res<MouseCoords>,
-    game_textures: Res<GameTextures>,
-
+    player_query: &Query<(Entity, &mut Transform), With<Player>>,
+    mouse_coords: &MouseCoords,
+    game_textures: &GameTextures,
+    obstacle_grid: &ObstacleGrid,
+    ranged: &RangedAttack,
 ) {
-    if let Ok((entity, transform)) = player_query.get_single() {
+    if let Ok((player_entity, transform)) = player_query.get_single() {
         let player_position = Vec2::new(transform.translation.x, transform.translation.y);
         let mouse_position = Vec2::new(mouse_coords.x, mouse_coords.y);
 
-
         // Calculate the direction from the player to the mouse
-        let direction = (mouse_position - player_position).normalize();
-
-        // Set the desired line length
-        let line_length = 1100.0;
+        let direction = (mouse_position - player_position).normalize_or_zero();
+
+        match ranged.kind {
+            RangedKind::Beam => {
+                // Cut the beam short at the first obstacle in its way
+                let max_length = 1100.0;
+                let line_length = if ranged.blocked_by_obstacles {
+                    first_obstacle_hit(obstacle_grid, player_position, player_position + direction * max_length, 0.)
+                        .map_or(max_length, |t| t * max_length)
+                } else {
+                    max_length
+                };
+
+                spawn_beam(commands, player_entity, player_position, direction, line_length, game_textures)
+            }
+            RangedKind::Projectile => {
+                // Fan multishot projectiles evenly around the aim direction
+                for i in 0..ranged.multishot {
+                    let offset = if ranged.multishot > 1 {
+                        ranged.spread * (i as f32 / (ranged.multishot - 1) as f32 - 0.5)
+                    } else {
+                        0.
+                    };
+
+                    spawn_projectile(
+                        commands,
+                        player_entity,
+                        player_position,
+                        Vec2::from_angle(offset).rotate(direction),
+                        ranged,
+                        game_textures);
+                }
+            }
+        }
+    }
+}
 
-        // Calculate the endpoint of the line
-        let end_point = player_position + direction * line_length;
+fn spawn_beam(
+    commands: &mut Commands,
+    player_entity: Entity,
+    player_position: Vec2,
+    direction: Vec2,
+    line_length: f32,
+    game_textures: &GameTextures,
+) {
+    // Calculate the endpoint of the line
+    let end_point = player_position + direction * line_length;
 
-        // Calculate the midpoint of the line for positioning the sprite
-        let midpoint = (player_position + end_point) / 2.0;
+    // Calculate the midpoint of the line for positioning the sprite
+    let midpoint = (player_position + end_point) / 2.0;
 
-        // Calculate the angle for proper rotation
-        let angle = direction.y.atan2(direction.x);
+    // Calculate the angle for proper rotation
+    let angle = direction.y.atan2(direction.x);
 
-        commands.spawn((
-                SpriteBundle {
-                    texture: game_textures.line.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(midpoint.x, midpoint.y, 1.),
-                        rotation: Quat::from_rotation_z(angle),
-                        scale: Vec3::new(1100.0, SPRITE_SCALE, 0.),
-                        ..Default::default()
-                    },
+    commands.spawn((
+            SpriteBundle {
+                texture: game_textures.line.clone(),
+                transform: Transform {
+                    translation: Vec3::new(midpoint.x, midpoint.y, 1.),
+                    rotation: Quat::from_rotation_z(angle),
+                    scale: Vec3::new(line_length, SPRITE_SCALE, 0.),
                     ..Default::default()
                 },
-                Collider::new(Vec2::new(line_length, SPRITE_SIZE.0)),
-                Line,
-                Lifetime {
-                    timer: Timer::from_seconds(0.1, TimerMode::Once),
+                ..Default::default()
+            },
+            Collider::new(Vec2::new(line_length, SPRITE_SIZE.0)),
+            Line,
+            Owner(player_entity),
+            AbilitySource(Ability::Ranged),
+            RunScope,
+            Lifetime {
+                timer: Timer::from_seconds(0.1, TimerMode::Once),
+            },
+    ));
+}
+
+fn spawn_projectile(
+    commands: &mut Commands,
+    player_entity: Entity,
+    player_position: Vec2,
+    direction: Vec2,
+    ranged: &RangedAttack,
+    game_textures: &GameTextures,
+) {
+    commands.spawn((
+            SpriteBundle {
+                texture: game_textures.line.clone(),
+                transform: Transform {
+                    translation: Vec3::new(player_position.x, player_position.y, 1.),
+                    rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+                    scale: Vec3::new(PROJECTILE_SIZE, PROJECTILE_SIZE / 4., 0.),
                 },
-        ));
+                ..Default::default()
+            },
+            Collider::new(Vec2::splat(PROJECTILE_SIZE)),
+            Owner(player_entity),
+            RunScope,
+            Projectile {
+                velocity: direction * ranged.speed,
+                damage: ranged.damage,
+                falloff: ranged.falloff,
+                pierce: ranged.pierce,
+                travelled: 0.,
+                max_range: ranged.max_range,
+                boomerang: ranged.boomerang,
+                returning: false,
+                blocked_by_obstacles: ranged.blocked_by_obstacles,
+                effects: ranged.effects.clone(),
+                hits: vec![],
+            },
+    ));
+}
+
+// Projectiles hit the boss as well as regular enemies
+type ProjectileTarget = (Attackable, Without<Player>, Without<Projectile>);
+
+// Everything a projectile can run into on its way
+#[derive(SystemParam)]
+struct ProjectileTargets<'w, 's> {
+    enemies: Query<'w, 's, (Entity, &'static Transform, &'static Collider), ProjectileTarget>,
+    obstacle_grid: Res<'w, ObstacleGrid>,
+}
+
+fn projectile_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_textures: Res<GameTextures>,
+    player_query: Query<&Transform, (With<Player>, Without<Projectile>)>,
+    mut projectile_query: Query<(Entity, &mut Transform, &mut Projectile, &Collider, &Owner), Without<Player>>,
+    targets: ProjectileTargets,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let player_position = player_query.get_single().ok().map(|transform| transform.translation.truncate());
+
+    for (entity, mut transform, mut projectile, collider, owner) in projectile_query.iter_mut() {
+        let start = transform.translation.truncate();
+
+        // A returning boomerang homes in on the player
+        if projectile.returning {
+            if let Some(player_position) = player_position {
+                let speed = projectile.velocity.length();
+                projectile.velocity = (player_position - start).normalize_or_zero() * speed;
+            }
+        }
+
+        let mut end = start + projectile.velocity * time.delta_seconds();
+        let mut spent = false;
+
+        // Stop at the first obstacle, enemies behind it are safe
+        if projectile.blocked_by_obstacles {
+            if let Some(t) = first_obstacle_hit(&targets.obstacle_grid, start, end, 0.) {
+                end = start.lerp(end, t);
+                spent = true;
+                spawn_impact(&mut commands, end, &game_textures);
+            }
+        }
+
+        for (enemy_entity, enemy_transform, enemy_collider) in targets.enemies.iter() {
+            if projectile.hits.contains(&enemy_entity) {
+                continue;
+            }
+
+            let enemy_rect = Rect::from_center_size(enemy_transform.translation.truncate(), enemy_collider.size)
+                .inflate(collider.size.x / 2.);
+
+            if segment_intersects_rect(start, end, enemy_rect) {
+                damage_events.send(DamageEvent {
+                    target: enemy_entity,
+                    source: Some(owner.0),
+                    ability: Some(Ability::Ranged),
+                    amount: projectile.damage,
+                    kind: DamageKind::Physical,
+                    effects: projectile.effects.clone(),
+                });
+                projectile.hits.push(enemy_entity);
+                spawn_impact(&mut commands, enemy_transform.translation.truncate(), &game_textures);
+
+                if projectile.pierce == 0 {
+                    spent = true;
+                    break;
+                }
+
+                // Every enemy pierced weakens the projectile, down to a minimum of 1 damage
+                projectile.pierce -= 1;
+                projectile.damage = ((projectile.damage as f32 * projectile.falloff).round() as i32).max(1);
+            }
+        }
+
+        transform.translation.x = end.x;
+        transform.translation.y = end.y;
+        transform.rotation = Quat::from_rotation_z(projectile.velocity.y.atan2(projectile.velocity.x));
+        projectile.travelled += start.distance(end);
+
+        if projectile.returning {
+            if player_position.is_none_or(|player_position| end.distance(player_position) < PROJECTILE_SIZE) {
+                spent = true;
+            }
+        } else if projectile.travelled >= projectile.max_range {
+            if projectile.boomerang {
+                // Head back, able to hit everything again on the way
+                projectile.returning = true;
+                projectile.hits.clear();
+            } else {
+                spent = true;
+            }
+        }
+
+        if spent {
+            commands.entity(entity).despawn();
+        }
     }
 }
 
+fn spawn_impact(
+    commands: &mut Commands,
+    position: Vec2,
+    game_textures: &GameTextures,
+) {
+    commands.spawn((
+            SpriteBundle {
+                texture: game_textures.line.clone(),
+                transform: Transform {
+                    translation: Vec3::new(position.x, position.y, 2.),
+                    rotation: Quat::from_rotation_z(PI / 4.),
+                    scale: Vec3::new(PROJECTILE_SIZE, PROJECTILE_SIZE, 0.),
+                },
+                ..Default::default()
+            },
+            Lifetime {
+                timer: Timer::from_seconds(0.1, TimerMode::Once),
+            },
+            RunScope,
+    ));
+}
+
 fn dash_attack(
     commands: &mut Commands,
-    mut query: Query<(Entity, &mut Transform), With<Player>>,
-    mouse_coords: Res<MouseCoords>,
-    game_textures: Res<GameTextures>,
+    query: &Query<(Entity, &mut Transform), With<Player>>,
+    mouse_coords: &MouseCoords,
 ) {
-    if let Ok((player_entity, mut transform)) = query.get_single_mut() {
+    if let Ok((player_entity, transform)) = query.get_single() {
         let player_position = Vec2::new(transform.translation.x, transform.translation.y);
         let mouse_position = Vec2::new(mouse_coords.x, mouse_coords.y);
         let direction = mouse_position - player_position;
-        let length = direction.length();
 
-        let midpoint = player_position + direction / 2.;
+        // Dash towards the cursor, but never further than the max dash distance
+        let distance = direction.length().min(DASH_DISTANCE);
 
-        let angle = direction.y.atan2(direction.x);
-
-        commands.spawn((
-                SpriteBundle {
-                    texture: game_textures.line.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(midpoint.x, midpoint.y, 0.),
-                        rotation: Quat::from_rotation_z(angle),
-                        scale: Vec3::new(length, SPRITE_SCALE, 0.),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-                Collider::new(Vec2::new(length, SPRITE_SIZE.0)),
-                Line,
-                Lifetime {
-                    timer: Timer::from_seconds(0.1, TimerMode::Once),
-                },
+        commands.entity(player_entity).insert((
+            Dash {
+                direction: direction.normalize_or_zero(),
+                remaining: distance,
+                hits: vec![],
+            },
+            Invulnerability {
+                timer: Timer::from_seconds(DASH_DURATION, TimerMode::Once)
+            },
         ));
-        commands.entity(player_entity).insert(Invulnerability {
-            timer: Timer::from_seconds(1.0, TimerMode::Once)
-        });
+    }
+}
+
+fn dash_movement_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &mut Transform, &mut Dash, &Collider), With<Player>>,
+    // Dashing through the boss hurts it like any other enemy
+    enemy_query: Query<(Entity, &Transform, &Collider), (Attackable, Without<Player>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    obstacle_grid: Res<ObstacleGrid>,
+) {
+    if let Ok((player_entity, mut transform, mut dash, player_collider)) = player_query.get_single_mut() {
+        let start = Vec2::new(transform.translation.x, transform.translation.y);
+        let mut step = (DASH_DISTANCE / DASH_DURATION * time.delta_seconds()).min(dash.remaining);
+
+        // Walls end the dash early instead of letting the player phase through
+        if let Some(t) = first_obstacle_hit(&obstacle_grid, start, start + dash.direction * step, player_collider.size.x / 2.) {
+            step *= t;
+            dash.remaining = step;
+        }
+
+        let end = start + dash.direction * step;
+
+        // Damage everything the player's body sweeps over this tick, each enemy once per dash
+        for (enemy_entity, enemy_transform, enemy_collider) in enemy_query.iter() {
+            if dash.hits.contains(&enemy_entity) {
+                continue;
+            }
 
-        transform.translation.x = mouse_position.x;
-        transform.translation.y = mouse_position.y;
+            let enemy_rect = Rect::from_center_size(enemy_transform.translation.truncate(), enemy_collider.size)
+                .inflate(player_collider.size.x / 2.);
+
+            if segment_intersects_rect(start, end, enemy_rect) {
+                damage_events.send(DamageEvent {
+                    target: enemy_entity,
+                    source: Some(player_entity),
+                    ability: Some(Ability::Dash),
+                    amount: 1,
+                    kind: DamageKind::Physical,
+                    effects: vec![StatusEffect::new(StatusKind::Stun, 0., DASH_STUN)],
+                });
+                dash.hits.push(enemy_entity);
+            }
+        }
+
+        transform.translation.x = end.x;
+        transform.translation.y = end.y;
+        dash.remaining -= step;
+
+        if dash.remaining <= 0. {
+            commands.entity(player_entity).remove::<Dash>();
+        }
     }
 }
 
 fn melee_attack(
     commands: &mut Commands,
-    player_query: Query<(Entity, &mut Transform), With<Player>>,
-    mouse_coords: Res<MouseCoords>,
-    game_textures: Res<GameTextures>,
-    mut points: ResMut<Points>,
+    player_query: &Query<(Entity, &mut Transform), With<Player>>,
+    mouse_coords: &MouseCoords,
+    game_textures: &GameTextures,
+    points: &mut Points,
 ) {
-    if let Ok((_, transform)) = player_query.get_single() {
+    if let Ok((player_entity, transform)) = player_query.get_single() {
         let player_position = Vec2::new(transform.translation.x, transform.translation.y);
         let mouse_position = Vec2::new(mouse_coords.x, mouse_coords.y);
 
@@ -264,6 +671,11 @@ fn melee_attack(
 
         points.0.clear();
 
+        // Deep cuts keep bleeding after the swing
+        let on_hit = OnHit {
+            effects: vec![StatusEffect::new(StatusKind::Bleed, 1., 2.)],
+        };
+
         for radius in (radius_step as i32..=max_radius as i32).step_by(radius_step as usize) {
             for i in 0..=arc_segments {
                 let angle = start_angle - (arc_span / 2.) + i as f32 * theta;
@@ -286,6 +698,10 @@ fn melee_attack(
                         },
                         Collider::new(Vec2::new(5., 5.)),
                         PointMarker,
+                        Owner(player_entity),
+                        AbilitySource(Ability::Attack),
+                        on_hit.clone(),
+                        RunScope,
                         Lifetime {
                             timer: Timer::from_seconds(0.1, TimerMode::Once),
                         },
@@ -298,11 +714,11 @@ fn melee_attack(
 
 fn aoe_attack(
     commands: &mut Commands,
-    player_query: Query<(Entity, &mut Transform), With<Player>>,
-    game_textures: Res<GameTextures>,
-    mut points: ResMut<Points>
+    player_query: &Query<(Entity, &mut Transform), With<Player>>,
+    game_textures: &GameTextures,
+    points: &mut Points,
 ) {
-    if let Ok((_, transform)) = player_query.get_single() {
+    if let Ok((player_entity, transform)) = player_query.get_single() {
         let player_position = Vec2::new(transform.translation.x, transform.translation.y);
 
         let max_radius = 300.;
@@ -313,6 +729,11 @@ fn aoe_attack(
 
         points.0.clear();
 
+        // The bladestorm leaves everything it touches sluggish
+        let on_hit = OnHit {
+            effects: vec![StatusEffect::new(StatusKind::Slow, 0.5, 3.)],
+        };
+
         for radius in(0..=max_radius as i32).step_by(radius_step as usize) {
             for i in 0..=arc_segments {
                 let angle = i as f32 * theta;
@@ -335,6 +756,10 @@ fn aoe_attack(
                         },
                         Collider::new(Vec2::new(5., 5.)),
                         PointMarker,
+                        Owner(player_entity),
+                        AbilitySource(Ability::Aoe),
+                        on_hit.clone(),
+                        RunScope,
                         Lifetime {
                             timer: Timer::from_seconds(0.1, TimerMode::Once),
                         },
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+use crate::components::{AppState, CameraController, RunScope};
+
+// Everything that happens when a run starts, in order
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RunSet {
+    // Old run entities are despawned and run resources go back to their defaults
+    Teardown,
+    // The new run's player, boss and world are spawned
+    Build,
+}
+
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(OnEnter(AppState::InRun), (RunSet::Teardown, RunSet::Build).chain())
+            .add_systems(OnEnter(AppState::InRun), (despawn_run_scope, reset_camera).in_set(RunSet::Teardown));
+    }
+}
+
+pub trait RunScopeAppExt {
+    // Registers a resource that is rebuilt from scratch at the start of every run
+    fn init_run_resource<R: Resource + FromWorld>(&mut self) -> &mut Self;
+}
+
+impl RunScopeAppExt for App {
+    fn init_run_resource<R: Resource + FromWorld>(&mut self) -> &mut Self {
+        self.init_resource::<R>()
+            .add_systems(OnEnter(AppState::InRun), reset_run_resource::<R>.in_set(RunSet::Teardown))
+    }
+}
+
+fn reset_run_resource<R: Resource + FromWorld>(world: &mut World) {
+    let resource = R::from_world(world);
+    world.insert_resource(resource);
+}
+
+fn despawn_run_scope(
+    mut commands: Commands,
+    query: Query<Entity, With<RunScope>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Snap the camera home so it doesn't sweep over from where the last run ended
+fn reset_camera(
+    mut query: Query<(&mut Transform, &mut OrthographicProjection, &mut CameraController)>,
+) {
+    for (mut transform, mut projection, mut controller) in query.iter_mut() {
+        *controller = CameraController::default();
+        transform.translation.x = 0.;
+        transform.translation.y = 0.;
+        transform.rotation = Quat::IDENTITY;
+        projection.scale = controller.min_zoom;
+    }
+}
@@ -0,0 +1,373 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::audio::{GlobalVolume, Volume};
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode};
+
+use crate::components::{AppState, BackgroundMusic, ButtonAction, MenuButton, MenuUI, PauseMenu, RunState, SettingField, Settings, SettingsMenu, UiTheme};
+use crate::events::ButtonPressed;
+use crate::widgets::{label, screen_column, spacer, ButtonBuilder};
+
+const CONFIG_DIR: &str = "gashadokuro_escape";
+const CONFIG_FILE: &str = "settings.cfg";
+
+const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+
+// Volumes go up in tenths and wrap back around to silent
+const VOLUME_STEPS: f32 = 10.;
+
+const ROW_WIDTH: f32 = 520.;
+
+// The main menu or pause overlay the settings screen opens over
+type UnderlyingMenu = Or<(With<MenuUI>, With<PauseMenu>)>;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .init_state::<SettingsMenu>()
+            .enable_state_scoped_entities::<SettingsMenu>()
+            .add_systems(OnEnter(SettingsMenu::Open), (spawn_settings_menu, hide_underlying_menus))
+            .add_systems(OnExit(SettingsMenu::Open), show_underlying_menus)
+            .add_systems(OnExit(AppState::Menu), close_settings)
+            .add_systems(OnExit(RunState::Paused), close_settings)
+            .add_systems(Update, (
+                    handle_settings_buttons,
+                    close_settings_on_escape.run_if(in_state(SettingsMenu::Open)),
+                    (
+                        refresh_setting_labels,
+                        apply_audio_settings,
+                        apply_window_settings,
+                        save_settings,
+                    ).run_if(resource_changed::<Settings>),
+            ).chain());
+    }
+}
+
+impl Settings {
+    // A missing or unreadable file just means the defaults
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return Settings::default();
+        };
+
+        fs::read_to_string(path)
+            .map(|contents| Settings::from_config(&contents))
+            .unwrap_or_default()
+    }
+
+    // One `key = value` per line, unknown keys and bad values fall back to the defaults
+    pub fn from_config(contents: &str) -> Self {
+        let mut settings = Settings::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim() {
+                "master_volume" => parse_into(value, &mut settings.master_volume),
+                "music_volume" => parse_into(value, &mut settings.music_volume),
+                "sfx_volume" => parse_into(value, &mut settings.sfx_volume),
+                "fullscreen" => parse_into(value, &mut settings.fullscreen),
+                "screen_shake" => parse_into(value, &mut settings.screen_shake),
+                "damage_numbers" => parse_into(value, &mut settings.damage_numbers),
+                "resolution" => {
+                    if let Some((width, height)) = value.split_once('x') {
+                        if let (Ok(width), Ok(height)) = (width.trim().parse(), height.trim().parse()) {
+                            settings.resolution = (width, height);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        settings.master_volume = settings.master_volume.clamp(0., 1.);
+        settings.music_volume = settings.music_volume.clamp(0., 1.);
+        settings.sfx_volume = settings.sfx_volume.clamp(0., 1.);
+        settings
+    }
+
+    pub fn to_config(&self) -> String {
+        format!(
+            "# Gashadokuro Escape settings\n\
+             master_volume = {}\n\
+             music_volume = {}\n\
+             sfx_volume = {}\n\
+             fullscreen = {}\n\
+             resolution = {}x{}\n\
+             screen_shake = {}\n\
+             damage_numbers = {}\n",
+            self.master_volume,
+            self.music_volume,
+            self.sfx_volume,
+            self.fullscreen,
+            self.resolution.0,
+            self.resolution.1,
+            self.screen_shake,
+            self.damage_numbers,
+        )
+    }
+
+    // Moves the field on to its next value
+    pub fn cycle(&mut self, field: SettingField) {
+        match field {
+            SettingField::MasterVolume => self.master_volume = next_volume(self.master_volume),
+            SettingField::MusicVolume => self.music_volume = next_volume(self.music_volume),
+            SettingField::SfxVolume => self.sfx_volume = next_volume(self.sfx_volume),
+            SettingField::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingField::Resolution => {
+                let index = RESOLUTIONS.iter().position(|resolution| *resolution == self.resolution);
+                self.resolution = RESOLUTIONS[index.map_or(0, |index| (index + 1) % RESOLUTIONS.len())];
+            }
+            SettingField::ScreenShake => self.screen_shake = !self.screen_shake,
+            SettingField::DamageNumbers => self.damage_numbers = !self.damage_numbers,
+        }
+    }
+
+    pub fn describe(&self, field: SettingField) -> String {
+        let on_off = |value: bool| if value { "On" } else { "Off" };
+
+        match field {
+            SettingField::MasterVolume => format!("Master Volume: {:.0}%", self.master_volume * 100.),
+            SettingField::MusicVolume => format!("Music Volume: {:.0}%", self.music_volume * 100.),
+            SettingField::SfxVolume => format!("Effects Volume: {:.0}%", self.sfx_volume * 100.),
+            SettingField::Fullscreen => format!("Fullscreen: {}", on_off(self.fullscreen)),
+            SettingField::Resolution => format!("Resolution: {}x{}", self.resolution.0, self.resolution.1),
+            SettingField::ScreenShake => format!("Screen Shake: {}", on_off(self.screen_shake)),
+            SettingField::DamageNumbers => format!("Damage Numbers: {}", on_off(self.damage_numbers)),
+        }
+    }
+}
+
+fn parse_into<T: std::str::FromStr>(value: &str, field: &mut T) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
+
+fn next_volume(volume: f32) -> f32 {
+    let step = (volume * VOLUME_STEPS).round() + 1.;
+    if step > VOLUME_STEPS { 0. } else { step / VOLUME_STEPS }
+}
+
+// The game's folder in the per user data dir, there is no platform crate so the usual locations are spelled out
+pub fn data_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+
+    let data_dir = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+    };
+
+    data_dir.map(|dir| dir.join(CONFIG_DIR))
+}
+
+fn config_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+fn spawn_settings_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    settings: Res<Settings>,
+) {
+    commands.spawn((
+            NodeBundle {
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.85).into(),
+                // Above the pause overlay and the HUD
+                z_index: ZIndex::Global(10),
+                ..screen_column()
+            },
+            StateScoped(SettingsMenu::Open),
+    ))
+        .with_children(|parent| {
+            label(parent, &theme, "Settings", 80.0);
+            spacer(parent, 20.0);
+
+            for field in SettingField::ALL {
+                ButtonBuilder::new(settings.describe(field), ButtonAction::ChangeSetting(field))
+                    .width(ROW_WIDTH)
+                    .spawn(parent, &theme);
+            }
+
+            spacer(parent, 20.0);
+            ButtonBuilder::new("Back", ButtonAction::CloseSettings).color(theme.danger_color).spawn(parent, &theme);
+        });
+}
+
+// Keeps the menu underneath out of sight and out of focus navigation
+fn hide_underlying_menus(
+    mut query: Query<&mut Visibility, UnderlyingMenu>,
+) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn show_underlying_menus(
+    mut query: Query<&mut Visibility, UnderlyingMenu>,
+) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn close_settings(mut next_state: ResMut<NextState<SettingsMenu>>) {
+    next_state.set(SettingsMenu::Closed);
+}
+
+fn close_settings_on_escape(
+    kb: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<SettingsMenu>>,
+) {
+    if kb.just_pressed(KeyCode::Escape) {
+        next_state.set(SettingsMenu::Closed);
+    }
+}
+
+fn handle_settings_buttons(
+    mut pressed_events: EventReader<ButtonPressed>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<SettingsMenu>>,
+) {
+    for ButtonPressed(action) in pressed_events.read() {
+        match action {
+            ButtonAction::OpenSettings => next_state.set(SettingsMenu::Open),
+            ButtonAction::CloseSettings => next_state.set(SettingsMenu::Closed),
+            ButtonAction::ChangeSetting(field) => settings.cycle(*field),
+            _ => {}
+        }
+    }
+}
+
+fn refresh_setting_labels(
+    settings: Res<Settings>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (button, children) in button_query.iter() {
+        let ButtonAction::ChangeSetting(field) = button.action else {
+            continue;
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = settings.describe(field);
+            }
+        }
+    }
+}
+
+// Master volume goes through bevy's global volume, which only applies to sounds started after it changes
+fn apply_audio_settings(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    music_query: Query<&AudioSink, With<BackgroundMusic>>,
+) {
+    global_volume.volume = Volume::new(settings.master_volume);
+
+    for sink in music_query.iter() {
+        sink.set_volume(settings.master_volume * settings.music_volume);
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    window.mode = if settings.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed };
+    window.resolution.set(settings.resolution.0 as f32, settings.resolution.1 as f32);
+}
+
+fn save_settings(settings: Res<Settings>) {
+    // Loading the file is not a change worth writing back
+    if settings.is_added() {
+        return;
+    }
+    let Some(path) = config_path() else {
+        return;
+    };
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Err(error) = fs::write(&path, settings.to_config()) {
+        warn!("Could not save settings to {}: {}", path.display(), error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_round_trips() {
+        let settings = Settings {
+            master_volume: 0.7,
+            music_volume: 0.3,
+            sfx_volume: 0.,
+            fullscreen: true,
+            resolution: (1920, 1080),
+            screen_shake: false,
+            damage_numbers: false,
+        };
+
+        assert_eq!(Settings::from_config(&settings.to_config()), settings);
+    }
+
+    #[test]
+    fn volumes_are_clamped() {
+        let settings = Settings::from_config("master_volume = 2.5\nmusic_volume = 0.4\nsfx_volume = -1\n");
+
+        assert_eq!(settings.master_volume, 1.);
+        assert_eq!(settings.music_volume, 0.4);
+        assert_eq!(settings.sfx_volume, 0.);
+    }
+
+    #[test]
+    fn bad_values_and_unknown_keys_keep_the_defaults() {
+        let settings = Settings::from_config(
+            "# comment = 3\nmaster_volume = loud\nfullscreen = yes\nresolution = 1920by1080\nvsync = true\nno equals sign\n",
+        );
+
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn volume_steps_up_in_tenths_and_wraps_to_silent() {
+        assert_eq!(next_volume(0.), 0.1);
+        assert_eq!(next_volume(0.3), 0.4);
+        assert_eq!(next_volume(0.9), 1.);
+        assert_eq!(next_volume(1.), 0.);
+    }
+
+    #[test]
+    fn resolution_cycles_through_the_list() {
+        let mut settings = Settings::default();
+        for _ in 0..RESOLUTIONS.len() {
+            settings.cycle(SettingField::Resolution);
+        }
+        assert_eq!(settings.resolution, Settings::default().resolution);
+
+        // A resolution from a hand edited file starts the cycle over
+        settings.resolution = (1000, 1000);
+        settings.cycle(SettingField::Resolution);
+        assert_eq!(settings.resolution, RESOLUTIONS[0]);
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::components::{AppState, IntroTimer, RunState, Score, UiTheme};
+use crate::widgets::label;
+use crate::{handle_escape_pressed, setup_game_over_screen, setup_menu, setup_pause_menu, won_game};
+
+const BOSS_INTRO_DURATION: f32 = 2.0;
+
+// Owns the state machine, every screen has exactly one OnEnter that builds it and
+// StateScoped takes it down again on exit
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .add_sub_state::<RunState>()
+            .enable_state_scoped_entities::<AppState>()
+            .enable_state_scoped_entities::<RunState>()
+            .add_systems(OnEnter(AppState::Menu), setup_menu)
+            .add_systems(OnEnter(AppState::GameOver), setup_game_over_screen)
+            .add_systems(OnEnter(AppState::Won), won_game)
+            .add_systems(OnEnter(RunState::BossIntro), setup_boss_intro)
+            .add_systems(OnEnter(AppState::Restarting), restart_run)
+            .add_systems(OnEnter(RunState::Paused), (setup_pause_menu, pause_time))
+            .add_systems(OnExit(RunState::Paused), unpause_time)
+            .add_systems(OnEnter(RunState::LevelUp), setup_level_up)
+            .add_systems(Update, (
+                    handle_escape_pressed.run_if(in_state(AppState::InRun)),
+                    update_boss_intro.run_if(in_state(RunState::BossIntro)),
+                    check_level_up.run_if(in_state(RunState::Playing)),
+                    continue_after_level_up.run_if(in_state(RunState::LevelUp)),
+            ));
+    }
+}
+
+fn banner(commands: &mut Commands, theme: &UiTheme, message: String, state: RunState) {
+    commands.spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            StateScoped(state),
+    ))
+        .with_children(|parent| {
+            label(parent, theme, message, 80.0);
+        });
+}
+
+fn restart_run(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InRun);
+}
+
+// Freeze virtual time outright, so timers don't catch up on the pause when play resumes
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn setup_boss_intro(mut commands: Commands, theme: Res<UiTheme>) {
+    commands.insert_resource(IntroTimer(Timer::from_seconds(BOSS_INTRO_DURATION, TimerMode::Once)));
+    banner(&mut commands, &theme, "Gashadokuro approaches...".to_string(), RunState::BossIntro);
+}
+
+fn update_boss_intro(
+    time: Res<Time>,
+    mut intro_timer: ResMut<IntroTimer>,
+    mut next_state: ResMut<NextState<RunState>>,
+) {
+    if intro_timer.0.tick(time.delta()).just_finished() {
+        next_state.set(RunState::Playing);
+    }
+}
+
+fn check_level_up(
+    mut score: ResMut<Score>,
+    mut next_state: ResMut<NextState<RunState>>,
+) {
+    if score.xp >= score.next_level_xp() {
+        score.level += 1;
+        next_state.set(RunState::LevelUp);
+    }
+}
+
+fn setup_level_up(mut commands: Commands, theme: Res<UiTheme>, score: Res<Score>) {
+    banner(&mut commands, &theme, format!("Level {}!\nPress Space to continue", score.level), RunState::LevelUp);
+}
+
+fn continue_after_level_up(
+    kb: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<RunState>>,
+) {
+    if kb.just_pressed(KeyCode::Space) {
+        next_state.set(RunState::Playing);
+    }
+}
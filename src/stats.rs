@@ -0,0 +1,228 @@
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::components::{Ability, Bigfoot, ButtonAction, Dash, Enemy, Player, RunState, RunStats, Score, UiTheme};
+use crate::events::{ButtonPressed, DamageApplied, EntityDied};
+use crate::run::RunScopeAppExt;
+use crate::settings::data_dir;
+
+const EXPORT_DIR: &str = "runs";
+
+const TABLE_FONT_SIZE: f32 = 26.0;
+const TABLE_LABEL_WIDTH: f32 = 300.0;
+const TABLE_VALUE_WIDTH: f32 = 140.0;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_run_resource::<RunStats>()
+            .add_systems(Update, export_stats)
+            .add_systems(Update, (
+                    record_damage,
+                    record_kills,
+                    record_dashes,
+                    record_movement,
+                    record_peak_enemies,
+                    record_wave_time,
+            ).run_if(in_state(RunState::Playing)));
+    }
+}
+
+impl RunStats {
+    pub fn total_kills(&self) -> u32 {
+        self.kills.values().sum::<u32>() + self.other_kills
+    }
+
+    pub fn to_json(&self) -> String {
+        let kills = Ability::ALL
+            .iter()
+            .map(|ability| format!("\"{}\": {}", ability, self.kills.get(ability).copied().unwrap_or(0)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let waves = self.wave_times
+            .iter()
+            .map(|time| format!("{:.2}", time))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "{{\n  \"kills\": {{ {}, \"Other\": {} }},\n  \"total_kills\": {},\n  \"damage_dealt\": {},\n  \"damage_taken\": {},\n  \"dashes\": {},\n  \"distance\": {:.1},\n  \"peak_enemies\": {},\n  \"bigfoot_damage\": {},\n  \"wave_times\": [{}]\n}}\n",
+            kills,
+            self.other_kills,
+            self.total_kills(),
+            self.damage_dealt,
+            self.damage_taken,
+            self.dashes,
+            self.distance,
+            self.peak_enemies,
+            self.bigfoot_damage,
+            waves,
+        )
+    }
+
+    // Label and value for every line of the breakdown table
+    fn rows(&self) -> Vec<(String, String)> {
+        let mut rows: Vec<(String, String)> = Ability::ALL
+            .iter()
+            .map(|ability| (format!("{} Kills", ability), self.kills.get(ability).copied().unwrap_or(0).to_string()))
+            .collect();
+
+        rows.push(("Other Kills".to_string(), self.other_kills.to_string()));
+        rows.push(("Damage Dealt".to_string(), self.damage_dealt.to_string()));
+        rows.push(("Damage Taken".to_string(), self.damage_taken.to_string()));
+        rows.push(("Dashes".to_string(), self.dashes.to_string()));
+        rows.push(("Distance Travelled".to_string(), format!("{:.0}", self.distance)));
+        rows.push(("Peak Enemies".to_string(), self.peak_enemies.to_string()));
+        rows.push(("Damage to Gashadokuro".to_string(), self.bigfoot_damage.to_string()));
+
+        for (wave, time) in self.wave_times.iter().enumerate() {
+            rows.push((format!("Wave {}", wave + 1), format!("{:.1}s", time)));
+        }
+
+        rows
+    }
+}
+
+// Two column breakdown of the run, used by the game over and victory screens
+pub fn spawn_stats_table(parent: &mut ChildBuilder, theme: &UiTheme, stats: &RunStats) {
+    parent.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            margin: UiRect::all(Val::Px(20.0)),
+            ..Default::default()
+        },
+        background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+        ..Default::default()
+    })
+    .with_children(|table| {
+        for (label, value) in stats.rows() {
+            table.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|row| {
+                row.spawn(TextBundle::from_section(label, theme.text_style(TABLE_FONT_SIZE)).with_style(Style {
+                    width: Val::Px(TABLE_LABEL_WIDTH),
+                    ..Default::default()
+                }));
+                row.spawn(TextBundle::from_section(value, theme.text_style(TABLE_FONT_SIZE)).with_style(Style {
+                    width: Val::Px(TABLE_VALUE_WIDTH),
+                    ..Default::default()
+                }));
+            });
+        }
+    });
+}
+
+fn record_damage(
+    mut applied_events: EventReader<DamageApplied>,
+    player_query: Query<(), With<Player>>,
+    bigfoot_query: Query<(), With<Bigfoot>>,
+    mut stats: ResMut<RunStats>,
+) {
+    for event in applied_events.read() {
+        if player_query.contains(event.target) {
+            stats.damage_taken += event.amount;
+        } else {
+            stats.damage_dealt += event.amount;
+        }
+
+        if bigfoot_query.contains(event.target) {
+            stats.bigfoot_damage += event.amount;
+        }
+    }
+}
+
+fn record_kills(
+    mut died_events: EventReader<EntityDied>,
+    mut stats: ResMut<RunStats>,
+) {
+    for event in died_events.read() {
+        if event.is_player {
+            continue;
+        }
+
+        match event.ability {
+            Some(ability) => *stats.kills.entry(ability).or_insert(0) += 1,
+            None => stats.other_kills += 1,
+        }
+    }
+}
+
+fn record_dashes(
+    query: Query<(), (Added<Dash>, With<Player>)>,
+    mut stats: ResMut<RunStats>,
+) {
+    stats.dashes += query.iter().count() as u32;
+}
+
+fn record_movement(
+    query: Query<&Transform, With<Player>>,
+    mut stats: ResMut<RunStats>,
+) {
+    let Ok(transform) = query.get_single() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+
+    if let Some(last_position) = stats.last_position {
+        stats.distance += position.distance(last_position);
+    }
+    stats.last_position = Some(position);
+}
+
+fn record_peak_enemies(
+    query: Query<(), With<Enemy>>,
+    mut stats: ResMut<RunStats>,
+) {
+    let count = query.iter().count() as u32;
+
+    if count > stats.peak_enemies {
+        stats.peak_enemies = count;
+    }
+}
+
+fn record_wave_time(
+    time: Res<Time>,
+    score: Res<Score>,
+    mut stats: ResMut<RunStats>,
+) {
+    // Levels start at 0, so the current wave is always at index `level`
+    while stats.wave_times.len() <= score.level as usize {
+        stats.wave_times.push(0.);
+    }
+
+    let wave = score.level as usize;
+    stats.wave_times[wave] += time.delta_seconds();
+}
+
+fn export_stats(
+    mut pressed_events: EventReader<ButtonPressed>,
+    stats: Res<RunStats>,
+) {
+    for ButtonPressed(action) in pressed_events.read() {
+        if *action != ButtonAction::ExportStats {
+            continue;
+        }
+        let Some(dir) = data_dir().map(|dir| dir.join(EXPORT_DIR)) else {
+            warn!("No data directory to export run stats to");
+            continue;
+        };
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+        let path = dir.join(format!("run-{}.json", timestamp));
+
+        let _ = fs::create_dir_all(&dir);
+        match fs::write(&path, stats.to_json()) {
+            Ok(()) => info!("Run stats exported to {}", path.display()),
+            Err(error) => warn!("Could not export run stats to {}: {}", path.display(), error),
+        }
+    }
+}
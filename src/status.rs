@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::components::{DamageKind, HitFlash, RunState, StatusEffects, StatusKind};
+use crate::events::DamageEvent;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (update_status_effects, status_tint_system).run_if(in_state(RunState::Playing)));
+    }
+}
+
+fn update_status_effects(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut StatusEffects)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut status_effects) in query.iter_mut() {
+        for effect in status_effects.effects.iter_mut() {
+            effect.timer.tick(time.delta());
+            effect.tick.tick(time.delta());
+
+            let damage_kind = match effect.kind {
+                StatusKind::Burn => Some(DamageKind::Fire),
+                StatusKind::Bleed => Some(DamageKind::Bleed),
+                _ => None,
+            };
+
+            if let Some(kind) = damage_kind {
+                if effect.tick.just_finished() {
+                    damage_events.send(DamageEvent {
+                        target: entity,
+                        source: None,
+                        ability: None,
+                        amount: effect.magnitude as i32,
+                        kind,
+                        effects: vec![],
+                    });
+                }
+            }
+        }
+
+        status_effects.effects.retain(|effect| !effect.timer.finished());
+    }
+}
+
+fn status_tint_system(
+    mut query: Query<(&StatusEffects, &mut Sprite), Without<HitFlash>>,
+) {
+    for (status_effects, mut sprite) in query.iter_mut() {
+        // Keep the current alpha so fades and flickers still work
+        let alpha = sprite.color.alpha();
+        sprite.color = status_effects.tint().unwrap_or(Color::WHITE).with_alpha(alpha);
+    }
+}
@@ -1,4 +1,5 @@
-use bevy::audio::{PlaybackMode, Volume};
+use bevy::audio::Volume;
+use bevy::ecs::system::SystemParam;
 use bevy::input::keyboard::Key;
 use bevy::input::mouse::{self, MouseMotion};
 use bevy::{prelude::*, time};
@@ -6,74 +7,37 @@ use bevy::sprite::MaterialMesh2dBundle;
 use bevy::transform::commands;
 use bevy::utils::HashSet;
 use bevy::window::PrimaryWindow;
-use bevy::ui::{AlignItems, JustifyContent, Val, UiRect, Style};
-use crate::components::{wallpaper, Ability, Bigfoot, BigfootState, Collider, CooldownUi, Cooldowns, GameOverUI, GameTimer, GameTimerText, GameUI, Health, HealthText, Invulnerability, Lifetime, Line, Map, MapGrid, MenuUI, MousePosition, MovementSpeed, PauseMenu, Player, PointMarker, Points, QuitButton, Resettable, RestartButton, Score, ScoreText, StartButton, Velocity};
-use crate::events::CollisionEvent;
-use crate::player::{self, player_spawn_system};
-use crate::{EnemySpawnRate, GameState, MAP_SPIRITE};
+use bevy::ui::{AlignItems, JustifyContent, Val, Style};
+use crate::components::{wallpaper, AppState, BackgroundMusic, Bigfoot, BigfootState, ButtonAction, CameraController, Collider, Combo, Cooldowns, Enemy, GameOverUI, GameTimer, GameTimerText, Health, HealthBar, HitInvulnerability, Invulnerability, Leaderboard, Lifetime, MaxHealth, MenuUI, PauseMenu, Player, PointValue, RunScope, RunState, RunStats, RunSummary, Score, Settings, SettingsMenu, TrackedThreat, UiTheme};
+use crate::leaderboard::spawn_rank_banner;
+use crate::stats::spawn_stats_table;
+use crate::widgets::{label, screen_column, spacer, ButtonBuilder};
+use crate::events::{EntityDied, ScreenShake};
+use crate::MAP_SPIRITE;
 
 use rand::Rng;
 use std::f32::consts::PI;
 use std::time::Duration;
-use crate::{GameTextures, MouseCoords, ENEMY_SPRITE, LINE_SPRITE, PLAYER_SPRITE};
+use crate::{GameTextures, BIGFOOT_HEALTH, BIGFOOT_HIT_INVULNERABILITY, MouseCoords, ENEMY_SPRITE, LINE_SPRITE, PLAYER_SPRITE};
 // Systems Implementation
 
-pub fn camera_follow_player(
-    mut param_set: ParamSet<(
-        Query<&Transform, With<Player>>,             // Query to get the player's position
-        Query<&mut Transform, With<Camera2d>>,       // Query to get the camera's Transform
-    )>,
-    window_query: Query<&Window>,                    // Query to get the window
-) {
-    // First, get the player's Transform
-    let player_position = {
-        if let Ok(player_transform) = param_set.p0().get_single() {
-            Some(player_transform.translation)
-        } else {
-            None
-        }
-    };
-
-    // If we have the player's position, continue
-    if let Some(player_position) = player_position {
-        // Then, get the window dimensions
-        if let Ok(window) = window_query.get_single() {
-            // Now we can safely get the camera's Transform
-            if let Ok(mut camera_transform) = param_set.p1().get_single_mut() {
-                let half_width = window.width() / 2.0;
-                let half_height = window.height() / 2.0;
-
-                // Calculate world bounds, ensuring min < max
-                let min_x = -500.0 + half_width;
-                let max_x = 500.0 - half_width;
-                let min_y = -500.0 + half_height;
-                let max_y = 500.0 - half_height;
-
-                // Ensure bounds are valid (min should be less than max)
-                if min_x < max_x && min_y < max_y {
-                    // Calculate camera position with clamping
-                    let camera_x = player_position.x.clamp(min_x, max_x);
-                    let camera_y = player_position.y.clamp(min_y, max_y);
-
-                    camera_transform.translation.x = camera_x;
-                    camera_transform.translation.y = camera_y;
-                } else {
-                    // Handle the edge case where bounds are not valid (e.g., world is smaller than window)
-                    camera_transform.translation.x = player_position.x;
-                    camera_transform.translation.y = player_position.y;
-                }
-            }
-        }
-    }
-}
-
 pub fn clean_dead(
 
     mut commands: Commands,
-    query: Query<(Entity, &Health)>,
+    mut died_events: EventReader<EntityDied>,
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    enemy_query: Query<(Entity, Option<&PointValue>), With<Enemy>>,
 ) {
-    for (entity_id, entity_health) in query.iter() {
-        if entity_health.hp <=0 {
+    // The player handles its own death, everything else is removed here
+    for event in died_events.read() {
+        if event.is_player {
+            continue;
+        }
+
+        if let Ok((entity_id, point_value)) = enemy_query.get(event.entity) {
+            let multiplier = combo.add_kill();
+            score.add_kill(point_value.map_or(1, |value| value.0) * multiplier);
             commands.entity(entity_id).despawn();
         }
     }
@@ -119,13 +83,25 @@ pub fn spawn_bigfoot(
                 Bigfoot {
                     timer: Timer::from_seconds(2.5, TimerMode::Once),
                     state: BigfootState::Invulnerable,
-                    health: 5,
                     x: player_position.x,  // Store the initial position
                     y: player_position.y,  // Store the initial position
                     airTexture: asset_server.load("foot.png"),
                     groundTexture: asset_server.load("foot_ground.png")
                 },
+                Health {
+                    hp: BIGFOOT_HEALTH,
+                },
+                MaxHealth(BIGFOOT_HEALTH),
+                // A short grace period after each hit so one swing's markers can't all land on the same stomp
+                HitInvulnerability(BIGFOOT_HIT_INVULNERABILITY),
+                HealthBar::new(120., 110.),
+                // Can't be hurt while it hovers
+                Invulnerability {
+                    timer: Timer::from_seconds(2.5, TimerMode::Once),
+                },
                 Collider::new(Vec2::new(256., 256.)),
+                TrackedThreat::new(Color::srgb(1.0, 0.6, 0.1)),
+                RunScope,
                 ));
     }
 }
@@ -136,8 +112,9 @@ pub fn update_bigfoot(
     mut player_query: Query<(&mut Transform, Option<&mut Invulnerability>), With<Player>>,
     time: Res<Time>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut state: ResMut<NextState<GameState>>,
+    sfx: SoundEffects,
+    mut state: ResMut<NextState<AppState>>,
+    mut shake_events: EventWriter<ScreenShake>,
 ) {
     for (entity, mut bigfoot, mut sprite, mut transform, mut texture) in query.iter_mut() {
         // Update Bigfoot's timer
@@ -148,6 +125,7 @@ pub fn update_bigfoot(
                 BigfootState::Invulnerable => {
                     // Switch to the stomp phase
                     bigfoot.state = BigfootState::Solid;
+                    commands.entity(entity).remove::<Invulnerability>();
 
                     // Make Bigfoot fully opaque and solid
                     sprite.color.set_alpha(1.0);
@@ -157,7 +135,8 @@ pub fn update_bigfoot(
 
                     // Change the texture based on the state
                     cycle_texture(&mut texture, &bigfoot);
-                    stomp_sound(&asset_server, &mut commands);
+                    stomp_sound(&sfx, &mut commands);
+                    shake_events.send(ScreenShake { trauma: 0.6 });
 
                     if let Ok((mut player_transform, mut invulnerability_option)) = player_query.get_single_mut() {
                         let player_position = Vec3 { x: player_transform.translation.x, y: player_transform.translation.y, z: 1.0 };
@@ -204,6 +183,9 @@ pub fn update_bigfoot(
                         // Reset Bigfoot's state to Invulnerable and restart the timer
                         bigfoot.state = BigfootState::Invulnerable;
                         bigfoot.timer = Timer::from_seconds(2.5, TimerMode::Once);
+                        commands.entity(entity).insert(Invulnerability {
+                            timer: bigfoot.timer.clone(),
+                        });
 
                         // Make Bigfoot semi-transparent again
                         sprite.color.set_alpha(0.5);
@@ -239,6 +221,21 @@ fn cycle_texture(
 //    }
 //}
 
+// Bringing the Gashadokuro down wins the run
+pub fn defeat_bigfoot(
+    mut commands: Commands,
+    mut died_events: EventReader<EntityDied>,
+    bigfoot_query: Query<(), With<Bigfoot>>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    for event in died_events.read() {
+        if bigfoot_query.contains(event.entity) {
+            commands.entity(event.entity).despawn();
+            state.set(AppState::Won);
+        }
+    }
+}
+
 pub fn update_bigfoot_position(
     mut bigfoot_query: Query<(&mut Bigfoot, &Transform)>,
 ) {
@@ -249,11 +246,12 @@ pub fn update_bigfoot_position(
 }
 
 
-pub fn setup_menu(mut commands:  Commands, asset_server:  Res<AssetServer>, player_query: Query<&Transform, With<Player>>,) {
+pub fn setup_menu(mut commands:  Commands, asset_server:  Res<AssetServer>, theme: Res<UiTheme>, player_query: Query<&Transform, With<Player>>,) {
 
-    if let Ok(player_transform) = player_query.get_single() {
-        let player_position = player_transform.translation;
+    // The player is spawned after the first menu, so fall back to the origin
+    let player_position = player_query.get_single().map(|transform| transform.translation).unwrap_or_default();
 
+    {
         commands.spawn(
             SpriteBundle {
                 texture: asset_server.load("./wallpaper.png"), // Assuming a texture is available
@@ -265,7 +263,8 @@ pub fn setup_menu(mut commands:  Commands, asset_server:  Res<AssetServer>, play
                 },
                 ..Default::default()
             },)
-        .insert(wallpaper);
+        .insert(wallpaper)
+        .insert(StateScoped(AppState::Menu));
 
     }
 
@@ -281,314 +280,60 @@ pub fn setup_menu(mut commands:  Commands, asset_server:  Res<AssetServer>, play
         ..Default::default()
     })
     .insert(GameOverUI)
+    .insert(StateScoped(AppState::Menu))
         .with_children(|parent| {
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          "Gashadokuro Escape",
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 120.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            })
-            .insert(wallpaper);
-
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          "Defeat the skeleton without being hit to win",
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 60.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            })
-            .insert(wallpaper);;
-
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          "WASD to Move around, Q to Melee, E for Ranged, T for AoE, F to Dash",
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 30.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            })
-            .insert(wallpaper);;
-            });
-    // Root node
-
-    commands.spawn(NodeBundle {
-        style: Style {
-            width: Val::Percent(100.0), 
-            height: Val::Percent(100.0),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            ..Default::default()
-        },
-        //background_color: Color::srgba(0.15, 0.15, 0.15, 1.0).into(),
-        ..Default::default()
+            label(parent, &theme, "Gashadokuro Escape", 120.0).insert(wallpaper);
+            label(parent, &theme, "Defeat the skeleton without being hit to win", 60.0).insert(wallpaper);
+            label(parent, &theme, "WASD to Move around, Q to Melee, E for Ranged, T for AoE, F to Dash", 30.0).insert(wallpaper);
+        });
 
-    }).insert(MenuUI)
-    //.insert(background_handle)
+    commands.spawn(screen_column())
+    .insert(MenuUI)
+    .insert(StateScoped(AppState::Menu))
     .with_children(|parent| {
-        // Start button
-        parent.spawn(ButtonBundle {
-            style: Style {
-                width: Val::Px(200.0), 
-                height: Val::Px(65.0),
-                margin: UiRect::all(Val::Px(10.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..Default::default()
-            },
-            background_color: Color::srgba(0.25, 0.25, 0.75, 1.0).into(),
-            ..Default::default()
-        })
-        .insert(StartButton)
-            .with_children(|parent| {
-                parent.spawn(TextBundle {
-                    text: Text::from_section(
-                              "Start",
-                              TextStyle {
-                                  font: asset_server.load("FiraSans-Bold.ttf"),
-                                  font_size: 40.0,
-                                  color: Color::WHITE,
-                              },
-                          ),
-                          ..Default::default()
-                });
-            });
-
-        // Quit button
-        parent.spawn(ButtonBundle {
-            style: Style {
-                width: Val::Px(200.0), 
-                height: Val::Px(65.0),
-                margin: UiRect::all(Val::Px(10.0)),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..Default::default()
-            },
-            background_color: Color::srgba(0.75, 0.25, 0.25, 1.0).into(),
-            ..Default::default()
-        })
-        .insert(QuitButton)
-            .with_children(|parent| {
-                parent.spawn(TextBundle {
-                    text: Text::from_section(
-                              "Quit",
-                              TextStyle {
-                                  font: asset_server.load("FiraSans-Bold.ttf"),
-                                  font_size: 40.0,
-                                  color: Color::WHITE,
-                              },
-                          ),
-                          ..Default::default()
-                });
-            });
+        ButtonBuilder::new("Start", ButtonAction::Start).spawn(parent, &theme);
+        ButtonBuilder::new("Settings", ButtonAction::OpenSettings).spawn(parent, &theme);
+        ButtonBuilder::new("Quit", ButtonAction::Quit).color(theme.danger_color).spawn(parent, &theme);
     });
 }
 
 pub fn setup_game_over_screen(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    score: Res<Score>,
-    game_timer: Res<GameTimer>,
+    theme: Res<UiTheme>,
+    run_summary: Res<RunSummary>,
+    run_stats: Res<RunStats>,
+    leaderboard: Res<Leaderboard>,
 ) {
-    let background_handle: Handle<Image> = asset_server.load("./wallpaper.png");
-
-    commands.spawn(NodeBundle {
-        style: Style {
-            width: Val::Percent(100.0), 
-            height: Val::Percent(100.0),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            flex_direction: FlexDirection::Column,  // Stack elements vertically
-            ..Default::default()
-        },
-        ..Default::default()
-    })
+    commands.spawn(screen_column())
     .insert(GameOverUI)
-        .insert(background_handle)
+    .insert(StateScoped(AppState::GameOver))
         .with_children(|parent| {
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          format!("You fell to Gashadokuru!"),
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 100.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          format!("Final Score: {}", score.get_enemies_killed()),
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 40.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
-
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          format!("Time Survived: {:.1} seconds", game_timer.0),
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 40.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
-
-            // Add a spacing node between the text and the buttons
-            parent.spawn(NodeBundle {
-                style: Style {
-                    height: Val::Px(50.0), // Spacing
-                    ..Default::default()
-                },
-                ..Default::default()
-            });
-
-            parent.spawn(ButtonBundle {
-                style: Style {
-                    width: Val::Px(200.0), 
-                    height: Val::Px(65.0),
-                    margin: UiRect::all(Val::Px(10.0)),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..Default::default()
-                },
-                background_color: Color::srgba(0.25, 0.75, 0.25, 1.0).into(),
-                ..Default::default()
-            })
-
-            .with_children(|parent| {
-                parent.spawn(TextBundle {
-                    text: Text::from_section(
-                              format!("Final Score: {}", score.get_enemies_killed()),
-                              TextStyle {
-                                  font: asset_server.load("FiraSans-Bold.ttf"),
-                                  font_size: 40.0,
-                                  color: Color::WHITE,
-                              },
-                          ),
-                          ..Default::default()
-                });
-            }).insert(RestartButton);
-            parent.spawn(ButtonBundle {
-                style: Style {
-                    width: Val::Px(200.0), 
-                    height: Val::Px(65.0),
-                    margin: UiRect::all(Val::Px(10.0)),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..Default::default()
-                },
-                background_color: Color::srgba(0.75, 0.25, 0.25, 0.5).into(),
-                ..Default::default()
-            })
-            .insert(QuitButton)
-                .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section(
-                                  format!("Time Survived: {:.1} seconds", game_timer.0),
-                                  TextStyle {
-                                      font: asset_server.load("FiraSans-Bold.ttf"),
-                                      font_size: 40.0,
-                                      color: Color::WHITE,
-                                  },
-                              ),
-                              ..Default::default()
-                    });
-
-                    // Add a spacing node between the text and the buttons
-                    parent.spawn(NodeBundle {
-                        style: Style {
-                            height: Val::Px(50.0), // Spacing
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-
-                    parent.spawn(ButtonBundle {
-                        style: Style {
-                            width: Val::Px(200.0), 
-                            height: Val::Px(65.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..Default::default()
-                        },
-                        background_color: Color::srgba(0.25, 0.75, 0.25, 1.0).into(),
-                        ..Default::default()
-                    })
-                    .insert(StartButton)
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle {
-                                text: Text::from_section(
-                                          "Play Again",
-                                          TextStyle {
-                                              font: asset_server.load("FiraSans-Bold.ttf"),
-                                              font_size: 40.0,
-                                              color: Color::WHITE,
-                                          },
-                                      ),
-                                      ..Default::default()
-                            });
-                        });
-                    parent.spawn(ButtonBundle {
-                        style: Style {
-                            width: Val::Px(200.0), 
-                            height: Val::Px(65.0),
-                            margin: UiRect::all(Val::Px(10.0)),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..Default::default()
-                        },
-                        background_color: Color::srgba(0.75, 0.25, 0.25, 0.5).into(),
-                        ..Default::default()
-                    })
-                    .insert(QuitButton)
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle {
-                                text: Text::from_section(
-                                          "Quit",
-                                          TextStyle {
-                                              font: asset_server.load("FiraSans-Bold.ttf"),
-                                              font_size: 40.0,
-                                              color: Color::WHITE,
-                                          },
-                                      ),
-                                      ..Default::default()
-                            });
-                        });
+            label(parent, &theme, "You fell to Gashadokuru!", 100.0);
+            spawn_rank_banner(parent, &theme, &leaderboard);
+            label(parent, &theme, format!("Final Score: {} ({} kills)", run_summary.points, run_summary.enemies_killed), 40.0);
+            label(parent, &theme, format!("Time Survived: {:.1} seconds", run_summary.time_survived), 40.0);
+            label(parent, &theme, format!("Extra Lives Used: {}", run_summary.lives_used), 40.0);
 
-                    // Other buttons and UI elements
-                });
+            end_of_run_panel(parent, &theme, &run_stats);
         });
 }
 
+// How the finished run went, for the victory screen
+#[derive(SystemParam)]
+pub struct RunResults<'w> {
+    score: Res<'w, Score>,
+    game_timer: Res<'w, GameTimer>,
+    run_stats: Res<'w, RunStats>,
+    leaderboard: Res<'w, Leaderboard>,
+}
+
 pub fn won_game(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    score: Res<Score>,
-    game_timer: Res<GameTimer>,
+    theme: Res<UiTheme>,
+    results: RunResults,
     player_query: Query<&Transform, With<Player>>,
 ) {
-    let background_handle: Handle<Image> = asset_server.load("./victory.png");
-
     if let Ok(player_transform) = player_query.get_single() {
         let player_position = player_transform.translation;
         commands.spawn(
@@ -602,288 +347,72 @@ pub fn won_game(
                 },
                 ..Default::default()
             },)
-        .insert(wallpaper);
+        .insert(wallpaper)
+        .insert(StateScoped(AppState::Won));
     }
-    commands.spawn(NodeBundle {
+
+    commands.spawn(screen_column())
+    .insert(GameOverUI)
+    .insert(StateScoped(AppState::Won))
+        .with_children(|parent| {
+            label(parent, &theme, "Gashadokuru Slain!", 100.0);
+            spawn_rank_banner(parent, &theme, &results.leaderboard);
+            label(parent, &theme, format!("Final Score: {} ({} kills)", results.score.points, results.score.get_enemies_killed()), 40.0);
+            label(parent, &theme, format!("Time Survived: {:.1} seconds", results.game_timer.0), 40.0);
+
+            end_of_run_panel(parent, &theme, &results.run_stats);
+        });
+}
+
+// Stats breakdown with the end of run buttons next to it
+fn end_of_run_panel(parent: &mut ChildBuilder, theme: &UiTheme, run_stats: &RunStats) {
+    parent.spawn(NodeBundle {
         style: Style {
-            width: Val::Percent(100.0), 
-            height: Val::Percent(100.0),
-            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Row,
             align_items: AlignItems::Center,
-            flex_direction: FlexDirection::Column,  // Stack elements vertically
             ..Default::default()
         },
         ..Default::default()
     })
-    .insert(GameOverUI)
-        .insert(background_handle)
+    .with_children(|parent| {
+        spawn_stats_table(parent, theme, run_stats);
 
-        .with_children(|parent| {
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          format!("Gashadokuru Slain!"),
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 100.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          format!("Final Score: {}", score.get_enemies_killed()),
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 40.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
-
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          format!("Time Survived: {:.1} seconds", game_timer.0),
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 40.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
-
-            // Add a spacing node between the text and the buttons
-            parent.spawn(NodeBundle {
-                style: Style {
-                    height: Val::Px(50.0), // Spacing
-                    ..Default::default()
-                },
-                ..Default::default()
-            });
-
-            parent.spawn(ButtonBundle {
-                style: Style {
-                    width: Val::Px(200.0), 
-                    height: Val::Px(65.0),
-                    margin: UiRect::all(Val::Px(10.0)),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..Default::default()
-                },
-                background_color: Color::srgba(0.25, 0.75, 0.25, 1.0).into(),
-                ..Default::default()
-            })
-
-            .with_children(|parent| {
-                parent.spawn(TextBundle {
-                    text: Text::from_section(
-                              "Play Again",
-                              TextStyle {
-                                  font: asset_server.load("FiraSans-Bold.ttf"),
-                                  font_size: 40.0,
-                                  color: Color::WHITE,
-                              },
-                          ),
-                          ..Default::default()
-                });
-            }).insert(RestartButton);
-            parent.spawn(ButtonBundle {
-                style: Style {
-                    width: Val::Px(200.0), 
-                    height: Val::Px(65.0),
-                    margin: UiRect::all(Val::Px(10.0)),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..Default::default()
-                },
-                background_color: Color::srgba(0.75, 0.25, 0.25, 0.5).into(),
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
                 ..Default::default()
-            })
-            .insert(QuitButton)
-                .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text::from_section(
-                                  "Quit",
-                                  TextStyle {
-                                      font: asset_server.load("FiraSans-Bold.ttf"),
-                                      font_size: 40.0,
-                                      color: Color::WHITE,
-                                  },
-                              ),
-                              ..Default::default()
-                    });
-                });
-
-            // Other buttons and UI elements
+            },
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            ButtonBuilder::new("Play Again", ButtonAction::Start).color(theme.confirm_color).spawn(parent, theme);
+            ButtonBuilder::new("Export Stats", ButtonAction::ExportStats).spawn(parent, theme);
+            ButtonBuilder::new("Quit", ButtonAction::Quit).color(theme.danger_color).spawn(parent, theme);
         });
-}
-
-pub fn check_won_game(
-    mut commands:  Commands,
-    query: Query<Entity, With<MenuUI>>,
-    state: ResMut<State<GameState>>,
-    mut asset_server:   Res<AssetServer>,
-    score: Res<Score>,
-    game_timer: Res<GameTimer>,
-    player_query: Query<&Transform, With<Player>>,
-) {
-    if *state.get() == GameState::Won {
-        println!("won");
-        won_game(commands, asset_server, score, game_timer, player_query);
-    }
-}
-
-
-pub fn spawn_menu(
-    mut commands:  Commands,
-    query: Query<Entity, With<MenuUI>>,
-    state: ResMut<State<GameState>>,
-    mut asset_server:   Res<AssetServer>,
-) {
-    if *state.get() == GameState::Running || *state.get() == GameState::Paused{
-        game_menus(&mut commands,  &mut asset_server);
-    }
-}
-
-pub fn despawn_menu(
-    mut commands: Commands,
-    query: Query<Entity, With<MenuUI>>,
-    mut state: ResMut<State<GameState>>,
-) {
-    if *state.get() == GameState::Running {
-        for entity in query.iter() {
-            commands.entity(entity).despawn_recursive();
-        }
-    }
-}
-
-pub fn kill_wallpaper(
-    mut commands: Commands,
-    query: Query<Entity, With<wallpaper>>,
-    mut state: ResMut<State<GameState>>,
-) {
-
-    for entity in query.iter() {
-        println!("herre");
-        commands.entity(entity).despawn_recursive();
-    }
-}
-
-pub fn kill_game_over_ui(
-    mut commands: Commands,
-    query: Query<Entity, With<GameOverUI>>,
-    mut state: ResMut<State<GameState>>,
-) {
-
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
-
-pub fn kill_game_ui(
-    mut commands: Commands,
-    query: Query<Entity, With<GameUI>>,
-    mut state: ResMut<State<GameState>>,
-) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
-
-pub fn menu_action_system(
-    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &StartButton), (Changed<Interaction>, With<Button>)>,
-    mut state: ResMut<NextState<GameState>>,
-    mut commands: Commands,
-    mut asset_server:  Res<AssetServer>,
-) {
-    for (interaction, mut color, _start_button) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Pressed => {
-                state.set(GameState::Reset);
-                menu_sound(&asset_server, &mut commands);
-            }
-            Interaction::Hovered => {
-                *color = BackgroundColor(Color::srgb(0.35, 0.75, 0.35));
-                menu_sound(&asset_server, &mut commands);
-            }
-            Interaction::None => {
-                *color = BackgroundColor(Color::srgb(0.25, 0.25, 0.75));
-            }
-        }
-    }
-}
-
-pub fn restart_action_system(
-    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &RestartButton), (Changed<Interaction>, With<Button>)>,
-    mut state: ResMut<NextState<GameState>>,
-    mut commands: Commands,
-    mut asset_server:  Res<AssetServer>,
-) {
-    for (interaction, mut color, _start_button) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Pressed => {
-                state.set(GameState::Reset);
-                menu_sound(&asset_server, &mut commands);
-            }
-            Interaction::Hovered => {
-                *color = BackgroundColor(Color::srgb(0.35, 0.75, 0.35));
-                menu_sound(&asset_server, &mut commands);
-            }
-            Interaction::None => {
-                *color = BackgroundColor(Color::srgb(0.25, 0.25, 0.75));
-            }
-        }
-    }
-}
-
-pub fn quit_action_system(
-    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &QuitButton), (Changed<Interaction>, With<Button>)>,
-    mut exit: EventWriter<AppExit>,
-    mut commands: Commands,
-    mut asset_server:  Res<AssetServer>,
-) {
-    for (interaction, mut color, _quit_button) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Pressed => {
-                menu_sound(&asset_server, &mut commands);
-                exit.send(AppExit::Success);
-            }
-            Interaction::Hovered => {
-                menu_sound(&asset_server, &mut commands);
-                *color = Color::srgb(0.75, 0.35, 0.35).into();
-            }
-            Interaction::None => {
-                *color = Color::srgb(0.75, 0.25, 0.25).into();
-            }
-        }
-    }
+    });
 }
 
 pub fn handle_escape_pressed(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut state: ResMut<NextState<GameState>>,
-    mut curr_state: ResMut<State<GameState>>,
+    run_state: Res<State<RunState>>,
+    mut next_run_state: ResMut<NextState<RunState>>,
+    settings_menu: Res<State<SettingsMenu>>,
     mut commands: Commands,
-    mut asset_server:  Res<AssetServer>,
-    query: Query<Entity, With<MenuUI>>,
+    sfx: SoundEffects,
 ) {
+    // Escape closes the settings screen first, the settings plugin takes care of that
+    if *settings_menu.get() == SettingsMenu::Open {
+        return;
+    }
+
     if keyboard_input.just_pressed(KeyCode::Escape) {
-        menu_sound(&asset_server, &mut commands);
-        println!("gaming");
-        if *curr_state.get() == GameState::Running {
-            state.set(GameState::Paused);
-        } else if *curr_state.get() == GameState::Paused {
-            state.set(GameState::Running);
+        menu_sound(&sfx, &mut commands);
+        match run_state.get() {
+            RunState::Playing => next_run_state.set(RunState::Paused),
+            RunState::Paused => next_run_state.set(RunState::Playing),
+            _ => {}
         }
-    }else if keyboard_input.just_pressed(KeyCode::KeyB) {
-        if *curr_state.get() == GameState::Paused {
-            state.set(GameState::Menu);
-            //despawn_menu(commands, query, asset_server.as_must);
-            //setup_menu(commands, asset_server);
-        }
-    }  
+    }
 }
 
 
@@ -964,9 +493,7 @@ pub fn update_cooldowns(
     mut query: Query<&mut Cooldowns>,
 ) {
     for mut cooldowns in query.iter_mut() {
-        for timer in cooldowns.cooldowns.values_mut() {
-            timer.tick(time.delta());
-        }
+        cooldowns.tick(time.delta());
     }
 }
 
@@ -984,194 +511,65 @@ pub fn update_lifetime(
     }
 }
 
-pub fn update_cooldowns_ui(
-    time: Res<Time>,
-    mut cooldowns_query: Query<&mut Cooldowns>,
-    mut text_query: Query<&mut Text, With<CooldownUi>>,
-) {
-    if let Ok(mut cooldowns) = cooldowns_query.get_single_mut() {
-        // Update the UI text for each ability
-        for (i, mut text) in text_query.iter_mut().enumerate() {
-            let ability_text = match i {
-                0 => format_cooldown_text("Attack", cooldowns.get_cooldown(Ability::Attack)),
-                1 => format_cooldown_text("Ranged", cooldowns.get_cooldown(Ability::Ranged)),
-                2 => format_cooldown_text("Dash", cooldowns.get_cooldown(Ability::Dash)),
-                3 => format_cooldown_text("Aoe", cooldowns.get_cooldown(Ability::Aoe)),
-                _ => "Unknown Ability".to_string(),
-            };
-
-            text.sections[0].value = ability_text;
-        }
-    }
-}
-
-fn format_cooldown_text(name: &str, cooldown: Option<f32>) -> String {
-    let display_time = cooldown.unwrap_or(0.0);
-    let display_time = if display_time > 0.0 {
-        display_time
-    } else {
-        0.0
-    };
-    format!("{}: {:.1}s", name, display_time)
-}
-
-pub fn update_ui_text(
-    player_query: Query<&Health, With<Player>>,
-    score: Res<Score>,
-    timer: Res<GameTimer>,
-    mut text_query: Query<(&mut Text, Option<&HealthText>, Option<&ScoreText>, Option<&GameTimerText>)>,
-) {
-    if let Ok(player_health) = player_query.get_single() {
-        for (mut text, health_text, score_text, timer_text) in text_query.iter_mut() {
-            if health_text.is_some() {
-                text.sections[0].value = format!("Health: {}", player_health.hp);
-            } else if score_text.is_some() {
-                text.sections[0].value = format!("Score: {}", score.get_enemies_killed());
-            }else if timer_text.is_some() {
-                text.sections[0].value = format!("Time: {}", f32::trunc(timer.0 * 100.0)/ 100.)
-            }
-        }
-    }
-}
-
-const MAP_WIDTH: f32 = 2672.0*4.0;
-const MAP_HEIGHT: f32 = 1312.0*4.0;
+pub const MAP_WIDTH: f32 = 2672.0*4.0;
+pub const MAP_HEIGHT: f32 = 1312.0*4.0;
 const MAP_SPAWN_THRESHOLD: f32 = 500.0; // Adjust as necessary
 
 
-pub fn check_and_spawn_map(
-    mut commands: Commands,
-    player_query: Query<&Transform, With<Player>>,
-    mut map_query: Query<(Entity, &Transform), With<Map>>,
-    mut map_grid: ResMut<MapGrid>,
-    game_textures: Res<GameTextures>,
-) {
-    if let Ok(player_transform) = player_query.get_single() {
-        let player_pos = player_transform.translation;
-
-        // Calculate the player's grid position
-        let player_grid_x = (player_pos.x / MAP_WIDTH).round() as i32;
-        let player_grid_y = (player_pos.y / MAP_HEIGHT).round() as i32;
-
-        // Check the surrounding 8 grid positions and spawn maps if necessary
-        for dx in -1..=1 {
-            for dy in -1..=1 {
-                let grid_x = player_grid_x + dx;
-                let grid_y = player_grid_y + dy;
-
-                if !map_grid.positions.contains(&(grid_x, grid_y)) {
-                    // Spawn a new map at this grid position
-                    commands.spawn((
-                            SpriteBundle {
-                                texture: game_textures.map.clone(),
-                                transform: Transform {
-                                    translation: Vec3::new(grid_x as f32 * MAP_WIDTH, grid_y as f32 * MAP_HEIGHT, 0.0),
-                                    scale: Vec3::new(4.0, 4.0, 0.0),
-                                    ..Default::default()
-                                },
-                                ..Default::default()
-                            },
-                            Map,
-                    ));
-                    // Add the new map's position to the grid
-                    map_grid.positions.insert((grid_x, grid_y));
-                }
-            }
-        }
-
-        // Now, clean up maps that are not adjacent to the player's position
-        for (entity, map_transform) in map_query.iter_mut() {
-            let map_pos = map_transform.translation;
-            let map_grid_x = (map_pos.x / MAP_WIDTH).round() as i32;
-            let map_grid_y = (map_pos.y / MAP_HEIGHT).round() as i32;
-
-            // Check if the map is within a 3x3 grid around the player
-            if (map_grid_x - player_grid_x).abs() > 1 || (map_grid_y - player_grid_y).abs() > 1 {
-                // Despawn maps that are outside this grid
-                commands.entity(entity).despawn();
-                map_grid.positions.remove(&(map_grid_x, map_grid_y));
-            }
-        }
-    }
-}
-
-pub fn show_pause_menu(mut query: Query<&mut Visibility, With<PauseMenu>>) {
-    for mut visibility in query.iter_mut() {
-        *visibility = Visibility::Visible;
-    }
-}
-
-pub fn hide_pause_menu(mut query: Query<&mut Visibility, With<PauseMenu>>) {
-    for mut visibility in query.iter_mut() {
-        *visibility = Visibility::Hidden;
-    }
-}
-
-pub fn setup_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn setup_pause_menu(mut commands: Commands, theme: Res<UiTheme>) {
     commands.spawn((
             NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    position_type: PositionType::Absolute,
-                    ..Default::default()
-                },
                 background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(), // Semi-transparent background
-                visibility: Visibility::Hidden, // Initially hidden
-                ..Default::default()
+                ..screen_column()
             },
             PauseMenu,
+            StateScoped(RunState::Paused),
     ))
         .with_children(|parent| {
-            parent.spawn(TextBundle {
-                text: Text::from_section(
-                          "Game Paused\nPress Esc to Resume\n\n\n 'B' To Go To Main Menu",
-                          TextStyle {
-                              font: asset_server.load("FiraSans-Bold.ttf"),
-                              font_size: 60.0,
-                              color: Color::WHITE,
-                          },
-                      ),
-                      ..Default::default()
-            });
+            label(parent, &theme, "Game Paused", 80.0);
+            spacer(parent, 30.0);
+
+            ButtonBuilder::new("Resume", ButtonAction::Resume).color(theme.confirm_color).spawn(parent, &theme);
+            ButtonBuilder::new("Restart Run", ButtonAction::Restart).spawn(parent, &theme);
+            ButtonBuilder::new("Settings", ButtonAction::OpenSettings).spawn(parent, &theme);
+            ButtonBuilder::new("Quit to Menu", ButtonAction::MainMenu).color(theme.danger_color).spawn(parent, &theme);
+        });
+}
+
+// What the sound helpers need to start a one-shot effect
+#[derive(SystemParam)]
+pub struct SoundEffects<'w> {
+    asset_server: Res<'w, AssetServer>,
+    settings: Res<'w, Settings>,
+}
+
+impl SoundEffects<'_> {
+    // The sink only exists a frame after the bundle spawns, so the effects volume goes in up front
+    fn play(&self, commands: &mut Commands, path: &str, volume: f32) {
+        commands.spawn(AudioBundle {
+            source: self.asset_server.load(path.to_string()),
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume * self.settings.sfx_volume)),
         });
+    }
 }
 
 pub fn menu_sound(
-    asset_server: &Res<AssetServer>,
-    commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/select.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(1.3),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, "./sfx/select.ogg", 1.3);
 }
 
 pub fn death_sound(
-    asset_server: &Res<AssetServer>,
-    commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/death.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(0.3),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, "./sfx/death.ogg", 0.3);
 }
 
 pub fn play_empty_swing(
-    asset_server: Res<AssetServer>,
-    mut commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
     let sound1 = "sfx/swing1.ogg";
     let sound2 = "sfx/swing2.ogg";
@@ -1186,20 +584,12 @@ pub fn play_empty_swing(
 
     // Select the sound based on the random index
     let selected_sound = sounds[random_index];
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load(selected_sound),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(1.0),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, selected_sound, 1.0);
 }
 
 pub fn play_hit_swing(
-    asset_server: & Res<AssetServer>,
-    commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
     let sound1 = "sfx/hit1.ogg";
     let sound2 = "sfx/hit2.ogg";
@@ -1214,104 +604,40 @@ pub fn play_hit_swing(
 
     // Select the sound based on the random index
     let selected_sound = sounds[random_index];
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load(selected_sound),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(1.0),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, selected_sound, 1.0);
 }
 
-pub fn bone_hit(
-    asset_server: &Res<AssetServer>,
-    commands: &mut Commands
+pub fn enemy_ouch_sound(
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/bone.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(0.15),
-            ..Default::default()
-        }
-    });
+    let sounds = ["sfx/enemy ouch 1.ogg", "sfx/enemy ouch 2.ogg", "sfx/enemy ouch 3.ogg"];
+
+    let mut rng = rand::thread_rng();
+    let random_index = rng.gen_range(0..sounds.len());
+
+    sfx.play(commands, sounds[random_index], 0.5);
 }
 
-pub fn dash_sound(
-    asset_server: &Res<AssetServer>,
-    commands: &mut Commands
+pub fn bone_hit(
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/dash.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(2.75),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, "./sfx/bone.ogg", 0.15);
 }
 
-pub fn reset_game(
-    mut commands: Commands,
-    mut player_query: Query<&mut Player>,
-    mut bigfoot_query: Query<&mut Bigfoot>,
-    enemy_query: Query<Entity, (With<Resettable>, Without<Player>)>,
-    mut score: ResMut<Score>,
-    mut game_timer: ResMut<GameTimer>,
-    mut state: ResMut<State<GameState>>,
-    mut next_state: ResMut<NextState<GameState>>,
-    player_transform: Query<&Transform, With<Player>>, 
-    asset_server: Res<AssetServer>,
-    mut cooldowns_query: Query<&mut Cooldowns>,
+pub fn dash_sound(
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Only proceed if the game state is Reset
-    if *state.get() == GameState::Reset {
-        // Reset player health and position
-        //if let Ok(mut player) = player_query.get_single_mut() {
-        //    player.health = 500;
-        //    player.x = 0.0;
-        //    player.y = 0.0;
-        //}
-
-        // Despawn all enemies with the Spawned tag
-        for enemy_entity in enemy_query.iter() {
-            commands.entity(enemy_entity).despawn_recursive();
-        }
-
-        if let Ok(mut cooldowns) = cooldowns_query.get_single_mut() {
-            cooldowns.reset_all();
-        }
-
-        // Spawn Bigfoot
-        spawn_bigfoot(commands, player_transform, asset_server);
-
-        // Reset score
-        score.reset();
-
-        // Reset game timer
-        game_timer.0 = 0.0;
-
-        // Transition back to the Running state
-        next_state.set(GameState::Running);
-    }
+    sfx.play(commands, "./sfx/dash.ogg", 2.75);
 }
 
 pub fn aoe_sound(
-    asset_server: &Res<AssetServer>,
-    commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/aoe.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(0.8),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, "./sfx/aoe.ogg", 0.8);
 }
 pub fn update_timer(
     time: Res<Time>,
@@ -1328,199 +654,28 @@ pub fn update_timer(
 }
 
 pub fn stomp_sound(
-    asset_server: &Res<AssetServer>,
-    commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/stomp.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(0.6),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, "./sfx/stomp.ogg", 0.6);
 }
 
 pub fn ranged_sound(
-    asset_server: &mut Res<AssetServer>,
-    commands: &mut Commands
+    sfx: &SoundEffects,
+    commands: &mut Commands,
 ) {
-    // Create an entity dedicated to playing our background music
-    let _ = &mut commands.spawn(AudioBundle {
-        source: asset_server.load("./sfx/ranged.ogg"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Once,
-            volume: Volume::new(0.6),
-            ..Default::default()
-        }
-    });
+    sfx.play(commands, "./sfx/ranged.ogg", 0.6);
 }
 
-pub fn game_menus(    commands: &mut Commands,
-    asset_server:  &mut Res<AssetServer>,) {
-    commands.spawn(
-        TextBundle::from_section(
-            "WASD to Move around, Q to Melee, E for Ranged, T for AoE, F to Dash",
-            TextStyle {
-                color: Color::WHITE,
-                ..default()
-            },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(12.),
-            left: Val::Px(12.),
-            ..default()
-        }),
-    ).insert(GameUI);
-
-    commands.spawn(NodeBundle {
-        style: Style {
-            width: Val::Percent(100.0), 
-            height: Val::Percent(100.0),
-            position_type: PositionType::Relative,
-            ..Default::default()
-        },
-        ..Default::default()
-    })
-    .insert(GameUI)
-        .with_children(|parent| {
-            // Health and Score container
-            parent.spawn(NodeBundle {
-                style: Style {
-                    position_type: PositionType::Absolute,
-                    top: Val::Px(10.0),
-                    left: Val::Px(10.0),
-                    flex_direction: FlexDirection::Column, // Stack vertically
-                    margin: UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Px(50.0), Val::Px(0.0)),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .insert(GameUI)
-                .with_children(|parent| {
-                    // Health Text
-                    parent.spawn(TextBundle {
-                        text: Text::from_section(
-
-                                  "Health: 100",
-                                  TextStyle {
-                                      font: asset_server.load("FiraSans-Bold.ttf"),
-                                      font_size: 40.0,
-                                      color: Color::WHITE,
-                                  },
-                              ),
-                              ..Default::default()
-                    })
-                    .insert(Resettable)
-                        .insert(HealthText)
-                        .insert(GameUI);
-
-                    parent.spawn(TextBundle {
-                        text: Text::from_section(
-                                  "Time: 0.0",
-                                  TextStyle {
-                                      font: asset_server.load("FiraSans-Bold.ttf"),
-                                      font_size: 40.0,
-                                      color: Color::WHITE,
-                                  },
-                              ),
-                              ..Default::default()
-                    })
-                    .insert(Resettable)
-
-                        .insert(GameTimerText)
-                        .insert(GameUI);
-
-                    // Score Text
-                    parent.spawn(TextBundle {
-                        text: Text::from_section(
-                                  "Score: 0",
-                                  TextStyle {
-                                      font: asset_server.load("FiraSans-Bold.ttf"),
-                                      font_size: 40.0,
-                                      color: Color::WHITE,
-                                  },
-                              ),
-                              style: Style {
-                                  //margin: UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Px(100.0), Val::Px(0.0)),
-                                  ..Default::default()
-                              },
-                              ..Default::default()
-                    })
-                    .insert(Resettable)
-                        .insert(ScoreText)
-                        .insert(GameUI);
-                    });
-
-            // Ability boxes container at the bottom
-            parent.spawn(NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0), 
-                    height: Val::Px(60.0), // 60px high for the ability boxes
-                    position_type: PositionType::Absolute,
-                    bottom: Val::Px(0.0), // Position at the bottom of the screen
-                    justify_content: JustifyContent::SpaceAround, // Evenly space ability boxes
-                    align_items: AlignItems::Center, // Center the boxes vertically within the container
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .with_children(|parent| {
-                let abilities = [
-                    Ability::Attack,
-                    Ability::Ranged,
-                    Ability::Dash,
-                    Ability::Aoe,
-                ];
-
-                for ability in abilities.iter() {
-                    let ability_name = match ability {
-                        Ability::Attack => "Attack",
-                        Ability::Ranged => "Ranged",
-                        Ability::Dash => "Dash",
-                        Ability::Aoe => "Bladestorm",
-                    };
-
-                    parent.spawn(NodeBundle {
-                        style: Style {
-                            width: Val::Percent(20.0),
-                            height: Val::Px(50.0), // 50px height for each ability box
-                            margin: UiRect::all(Val::Px(5.0)),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..Default::default()
-                        },
-                        background_color: Color::srgba(0.9, 0.9, 0.9, 0.5).into(),
-                        ..Default::default()
-                    })
-                    .with_children(|parent| {
-                        parent.spawn(TextBundle {
-                            text: Text::from_section(
-                                      format!("{}: {:.1}s", ability_name, 0.0), // Ability name and placeholder cooldown
-                                      TextStyle {
-                                          font: asset_server.load("FiraSans-Bold.ttf"),
-                                          font_size: 30.0,
-                                          color: Color::BLACK,
-                                      },
-                                  ),
-                                  ..Default::default()
-                        })
-                        .insert(CooldownUi)
-                            .insert(Resettable)
-                            .insert(GameUI);
-                        });
-                }
-            });
-        });
+pub fn boomerang_sound(
+    sfx: &SoundEffects,
+    commands: &mut Commands,
+) {
+    sfx.play(commands, "./sfx/boomerang 2.ogg", 0.6);
 }
 
-
-
-pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut state: ResMut<NextState<GameState>>) {
-    commands.spawn(Camera2dBundle::default());
-    state.set(GameState::Menu);
+pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    commands.spawn((Camera2dBundle::default(), CameraController::default()));
 
 
     let game_textures = GameTextures {
@@ -1530,53 +685,21 @@ pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut state:
         map: asset_server.load(MAP_SPIRITE),
     };
 
-    let enemy_count = EnemySpawnRate(2.0);
-
     let mouse_coords = MouseCoords {
         x: 0.,
         y: 0.,
     };
 
     // Create an entity dedicated to playing our background music
-    commands.spawn(AudioBundle {
-        source: asset_server.load("./beats/back.ogg"),
-        settings: PlaybackSettings::LOOP,
-    });
     commands.spawn((
-            SpriteBundle {
-                texture: game_textures.map.clone(),
-                transform: Transform { 
-                    //translation: Vec3::new(0., SPRITE_SIZE.1 / 2. + 5., 10.),
-                    scale: Vec3::new(4., 4., 0.),
-                    ..Default::default()
-                },
-                ..Default::default()
+            AudioBundle {
+                source: asset_server.load("./beats/back.ogg"),
+                // Master volume is applied through GlobalVolume when the sink is created
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new(settings.music_volume)),
             },
-    )).insert(Map)
-        .insert(Resettable);
+            BackgroundMusic,
+    ));
 
     commands.insert_resource(game_textures);
-    commands.insert_resource(enemy_count);
     commands.insert_resource(mouse_coords);
-    commands.insert_resource(Points::default());
-}
-
-pub fn cleanup_game(mut commands:   Commands, 
-    mut asset_server:   Res<AssetServer>, 
-    player_query: Query<&Transform, With<Player>>, 
-    mut score: ResMut<Score>, 
-    mut points: ResMut<Points>, 
-    mut state: ResMut<NextState<GameState>>,
-    mut game_textures: Res<GameTextures>, 
-    query: Query<Entity, (With<Resettable>)>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
-    }
-    score.reset();
-    *points = Points::default();
-
-    //game_menus( &mut commands, &mut asset_server);
-    spawn_bigfoot(commands, player_query, asset_server);
-    // player_spawn_system(commands, game_textures);
-
 }
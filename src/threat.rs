@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::components::{AppState, Bigfoot, BigfootState, CameraController, RunState, ThreatArrow, ThreatIndicator, TrackedThreat};
+
+const ARROW_PIXELS: u32 = 32;
+const ARROW_SIZE: f32 = 36.;
+
+// Keeps arrows off the very edge of the window
+const EDGE_MARGIN: f32 = 28.;
+
+// Arrows shrink from full size right outside the view to this scale far away
+const MIN_SCALE: f32 = 0.5;
+const FAR_DISTANCE: f32 = 3000.;
+
+// How fast and how much an arrow pulses at full wind-up
+const PULSE_SPEED: f32 = 12.;
+const PULSE_STRENGTH: f32 = 0.35;
+
+// Each arrow is positioned, turned, shown and tinted every frame
+type ArrowNode = (
+    Entity,
+    &'static ThreatIndicator,
+    &'static mut Style,
+    &'static mut Transform,
+    &'static mut Visibility,
+    &'static mut UiImage,
+);
+
+pub struct ThreatPlugin;
+
+impl Plugin for ThreatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_threat_arrow)
+            .add_systems(OnExit(RunState::Playing), hide_threat_indicators)
+            .add_systems(Update, (
+                    bigfoot_wind_up,
+                    spawn_threat_indicators,
+                    update_threat_indicators.after(spawn_threat_indicators).after(bigfoot_wind_up),
+            ).run_if(in_state(RunState::Playing)));
+    }
+}
+
+// White triangle pointing right, tinted per threat
+fn create_threat_arrow(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: ARROW_PIXELS,
+            height: ARROW_PIXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+
+    let half = ARROW_PIXELS as f32 / 2.;
+    for y in 0..ARROW_PIXELS {
+        for x in 0..ARROW_PIXELS {
+            if (y as f32 + 0.5 - half).abs() <= (ARROW_PIXELS - x) as f32 / 2. {
+                let index = ((y * ARROW_PIXELS + x) * 4) as usize;
+                image.data[index..index + 4].copy_from_slice(&[255, 255, 255, 255]);
+            }
+        }
+    }
+
+    commands.insert_resource(ThreatArrow(images.add(image)));
+}
+
+// Bigfoot winds up while hovering, right before it stomps
+fn bigfoot_wind_up(
+    mut query: Query<(&Bigfoot, &mut TrackedThreat)>,
+) {
+    for (bigfoot, mut threat) in query.iter_mut() {
+        threat.wind_up = match bigfoot.state {
+            BigfootState::Invulnerable => bigfoot.timer.fraction(),
+            _ => 0.,
+        };
+    }
+}
+
+fn spawn_threat_indicators(
+    mut commands: Commands,
+    arrow: Res<ThreatArrow>,
+    query: Query<(Entity, &TrackedThreat), Added<TrackedThreat>>,
+) {
+    for (entity, threat) in query.iter() {
+        commands.spawn((
+                ImageBundle {
+                    image: UiImage::new(arrow.0.clone()).with_color(threat.color),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(ARROW_SIZE),
+                        height: Val::Px(ARROW_SIZE),
+                        ..Default::default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+                ThreatIndicator { target: entity },
+                StateScoped(AppState::InRun),
+        ));
+    }
+}
+
+fn update_threat_indicators(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    threat_query: Query<(&Transform, &TrackedThreat)>,
+    mut indicator_query: Query<ArrowNode, Without<TrackedThreat>>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+    let camera_position = camera_transform.translation().truncate();
+
+    for (entity, indicator, mut style, mut transform, mut visibility, mut image) in indicator_query.iter_mut() {
+        // The threat is gone, so is its arrow
+        let Ok((threat_transform, threat)) = threat_query.get(indicator.target) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let world_position = threat_transform.translation;
+        let Some(screen_position) = camera.world_to_viewport(camera_transform, world_position) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let on_screen = screen_position.x >= 0. && screen_position.y >= 0.
+            && screen_position.x <= viewport.x && screen_position.y <= viewport.y;
+        if on_screen {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        // Push the arrow along the line from the centre until it meets the inset screen edge
+        let center = viewport / 2.;
+        let direction = (screen_position - center).normalize_or_zero();
+        let half_extent = center - EDGE_MARGIN;
+        let reach = (half_extent / direction.abs().max(Vec2::splat(f32::EPSILON))).min_element();
+        let edge_position = center + direction * reach;
+
+        let distance = world_position.truncate().distance(camera_position);
+        let distance_scale = 1. - (1. - MIN_SCALE) * (distance / FAR_DISTANCE).min(1.);
+        let pulse = 1. + PULSE_STRENGTH * threat.wind_up * (time.elapsed_seconds() * PULSE_SPEED).sin().abs();
+        let size = ARROW_SIZE * distance_scale * pulse;
+
+        style.left = Val::Px(edge_position.x - size / 2.);
+        style.top = Val::Px(edge_position.y - size / 2.);
+        style.width = Val::Px(size);
+        style.height = Val::Px(size);
+
+        // UI space grows downwards like the viewport, so the angle carries over as is
+        transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+        image.color = threat.color.mix(&Color::srgb(1.0, 0.1, 0.1), threat.wind_up);
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_threat_indicators(
+    mut query: Query<&mut Visibility, With<ThreatIndicator>>,
+) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
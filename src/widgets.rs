@@ -0,0 +1,258 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::components::{AppState, ButtonAction, MenuButton, RunState, UiFocus, UiTheme};
+use crate::events::ButtonPressed;
+use crate::{menu_sound, SoundEffects};
+
+const FONT: &str = "FiraSans-Bold.ttf";
+
+pub struct WidgetPlugin;
+
+impl Plugin for WidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiTheme>()
+            .init_resource::<UiFocus>()
+            .add_event::<ButtonPressed>()
+            .add_systems(Update, (
+                    button_interaction_system,
+                    navigate_focus,
+                    focus_sound,
+                    color_buttons,
+                    apply_button_actions,
+            ).chain());
+    }
+}
+
+impl FromWorld for UiTheme {
+    fn from_world(world: &mut World) -> Self {
+        UiTheme {
+            font: world.resource::<AssetServer>().load(FONT),
+            text_color: Color::WHITE,
+            button_color: Color::srgb(0.25, 0.25, 0.75),
+            confirm_color: Color::srgb(0.25, 0.75, 0.25),
+            danger_color: Color::srgb(0.75, 0.25, 0.25),
+            focus_lightness: 0.1,
+            button_size: Vec2::new(200.0, 65.0),
+            button_font_size: 40.0,
+        }
+    }
+}
+
+impl UiTheme {
+    pub fn text_style(&self, font_size: f32) -> TextStyle {
+        TextStyle {
+            font: self.font.clone(),
+            font_size,
+            color: self.text_color,
+        }
+    }
+}
+
+// Full screen column that centers whatever is put in it
+pub fn screen_column() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+pub fn label<'a>(parent: &'a mut ChildBuilder, theme: &UiTheme, text: impl Into<String>, font_size: f32) -> EntityCommands<'a> {
+    parent.spawn(TextBundle::from_section(text, theme.text_style(font_size)).with_text_justify(JustifyText::Center))
+}
+
+pub fn spacer(parent: &mut ChildBuilder, height: f32) {
+    parent.spawn(NodeBundle {
+        style: Style {
+            height: Val::Px(height),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+}
+
+pub struct ButtonBuilder {
+    label: String,
+    action: ButtonAction,
+    color: Option<Color>,
+    width: Option<f32>,
+}
+
+impl ButtonBuilder {
+    pub fn new(label: impl Into<String>, action: ButtonAction) -> Self {
+        ButtonBuilder {
+            label: label.into(),
+            action,
+            color: None,
+            width: None,
+        }
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn spawn<'a>(self, parent: &'a mut ChildBuilder, theme: &UiTheme) -> EntityCommands<'a> {
+        let color = self.color.unwrap_or(theme.button_color);
+
+        let mut button = parent.spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(self.width.unwrap_or(theme.button_size.x)),
+                        height: Val::Px(theme.button_size.y),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    background_color: color.into(),
+                    ..Default::default()
+                },
+                MenuButton {
+                    action: self.action,
+                    color,
+                },
+        ));
+
+        button.with_children(|parent| {
+            parent.spawn(TextBundle::from_section(self.label, theme.text_style(theme.button_font_size)));
+        });
+
+        button
+    }
+}
+
+// The mouse moves focus around like the keyboard does and clicking activates
+fn button_interaction_system(
+    query: Query<(Entity, &Interaction, &MenuButton), Changed<Interaction>>,
+    mut focus: ResMut<UiFocus>,
+    mut pressed_events: EventWriter<ButtonPressed>,
+) {
+    for (entity, interaction, button) in query.iter() {
+        match *interaction {
+            Interaction::Pressed => {
+                focus.set_if_neq(UiFocus(Some(entity)));
+                pressed_events.send(ButtonPressed(button.action));
+            }
+            Interaction::Hovered => {
+                focus.set_if_neq(UiFocus(Some(entity)));
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+// Up and down walk the buttons in screen order, confirm activates the focused one
+fn navigate_focus(
+    kb: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    query: Query<(Entity, &GlobalTransform, &ViewVisibility, &MenuButton)>,
+    mut focus: ResMut<UiFocus>,
+    mut pressed_events: EventWriter<ButtonPressed>,
+) {
+    let mut buttons: Vec<(Entity, Vec3, ButtonAction)> = query
+        .iter()
+        .filter(|(_, _, visibility, _)| visibility.get())
+        .map(|(entity, transform, _, button)| (entity, transform.translation(), button.action))
+        .collect();
+    buttons.sort_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)));
+
+    let current = focus.0.and_then(|entity| buttons.iter().position(|button| button.0 == entity));
+    if current.is_none() && focus.0.is_some() {
+        // The focused button went away with its screen
+        focus.0 = None;
+    }
+    if buttons.is_empty() {
+        return;
+    }
+
+    let gamepad_pressed = |button_type: GamepadButtonType| {
+        gamepads.iter().any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    let next = kb.just_pressed(KeyCode::ArrowDown) || kb.just_pressed(KeyCode::Tab) || gamepad_pressed(GamepadButtonType::DPadDown);
+    let previous = kb.just_pressed(KeyCode::ArrowUp) || gamepad_pressed(GamepadButtonType::DPadUp);
+    let confirm = kb.just_pressed(KeyCode::Enter) || gamepad_pressed(GamepadButtonType::South);
+
+    if next || previous {
+        let index = match current {
+            Some(index) if next => (index + 1) % buttons.len(),
+            Some(index) => (index + buttons.len() - 1) % buttons.len(),
+            None => 0,
+        };
+        focus.set_if_neq(UiFocus(Some(buttons[index].0)));
+    } else if confirm {
+        if let Some(index) = current {
+            pressed_events.send(ButtonPressed(buttons[index].2));
+        }
+    }
+}
+
+// Only fires when focus lands on a different button, not on every hover event
+fn focus_sound(
+    mut commands: Commands,
+    sfx: SoundEffects,
+    focus: Res<UiFocus>,
+) {
+    if focus.is_changed() && focus.0.is_some() {
+        menu_sound(&sfx, &mut commands);
+    }
+}
+
+fn color_buttons(
+    theme: Res<UiTheme>,
+    focus: Res<UiFocus>,
+    mut query: Query<(Entity, &Interaction, &MenuButton, &mut BackgroundColor)>,
+) {
+    for (entity, interaction, button, mut background) in query.iter_mut() {
+        let color = match *interaction {
+            Interaction::Pressed => button.color.darker(theme.focus_lightness),
+            _ if focus.0 == Some(entity) => button.color.lighter(theme.focus_lightness),
+            _ => button.color,
+        };
+
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+fn apply_button_actions(
+    mut commands: Commands,
+    sfx: SoundEffects,
+    mut pressed_events: EventReader<ButtonPressed>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_run_state: ResMut<NextState<RunState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for ButtonPressed(action) in pressed_events.read() {
+        menu_sound(&sfx, &mut commands);
+
+        match action {
+            ButtonAction::Start => next_state.set(AppState::InRun),
+            ButtonAction::Quit => {
+                exit.send(AppExit::Success);
+            }
+            ButtonAction::Resume => next_run_state.set(RunState::Playing),
+            ButtonAction::Restart => next_state.set(AppState::Restarting),
+            ButtonAction::MainMenu => next_state.set(AppState::Menu),
+            // Settings buttons are handled by the settings plugin
+            _ => {}
+        }
+    }
+}